@@ -0,0 +1,213 @@
+use std::fs;
+use std::io::{self, Read};
+
+use log::info;
+use regex::Regex;
+use serde::Deserialize;
+use serde_yaml::Value;
+
+use crate::{keys, Configuration, Engine};
+
+
+/// Operation on the configuration, read from an entry with an `op` key. Entries without one are engine definitions,
+/// added or replacing the engine configured under the same name.
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case", deny_unknown_fields)]
+enum Operation {
+    /// Adds the engine, failing if one is already configured under its name
+    Add { engine: Engine },
+
+    /// Replaces the engine configured under the same name, failing if there's none
+    Replace { engine: Engine },
+
+    /// Removes the engine configured under exactly the name, archiving it unless `purge` is given
+    Remove {
+        name: String,
+
+        #[serde(default)]
+        purge: bool,
+    },
+
+    /// Sets the setting named by the dotted key, as `config set` does
+    Set { key: String, value: Value },
+}
+
+
+/// Reads the entries from the file, or from the standard input if the path is `-`
+fn read(source: &str) -> Result<String, io::Error> {
+    if source == "-" {
+        let mut content = String::new();
+        io::stdin().read_to_string(&mut content)?;
+        Ok(content)
+    } else {
+        fs::read_to_string(source)
+    }
+}
+
+
+/// Fails if the regex of the engine does not compile or the charset of its encoding is unknown, since the engine
+/// would be unable to generate any URL
+fn validate(engine: &Engine) -> Result<(), io::Error> {
+    Regex::new(&engine.regex)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid regex of {}: {}", engine.name, e)))?;
+    match &engine.encoding {
+        Some(encoding) => encoding.charset()
+            .map(|_| ())
+            .map_err(|e| io::Error::new(e.kind(), format!("Invalid encoding of {}: {}", engine.name, e))),
+        None => Ok(()),
+    }
+}
+
+
+/// Applies a single entry to the configuration, returning the line describing the change
+fn apply_entry(config: &mut Configuration, entry: Value) -> Result<String, io::Error> {
+    let invalid = |e: serde_yaml::Error| io::Error::new(io::ErrorKind::InvalidInput, e);
+
+    if entry.get("op").is_none() {
+        let engine: Engine = serde_yaml::from_value(entry).map_err(invalid)?;
+        validate(&engine)?;
+        let line = if config.engines.named(&engine.name).is_some() { format!("~ {}", engine.name) } else { format!("+ {}", engine.name) };
+        config.replace(engine);
+        return Ok(line);
+    }
+
+    match serde_yaml::from_value(entry).map_err(invalid)? {
+        Operation::Add { engine } => {
+            if config.contains(&engine.name) {
+                return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("The engine {} is already configured", engine.name)));
+            }
+            validate(&engine)?;
+            let line = format!("+ {}", engine.name);
+            config.push(engine)?;
+            Ok(line)
+        }
+        Operation::Replace { engine } => {
+            let previous = config.engines.named(&engine.name).cloned().ok_or_else(|| io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("There is no engine named {} to replace", engine.name),
+            ))?;
+            validate(&engine)?;
+            let line = format!("~ {}", previous.name);
+            config.engines.modify(&previous.name, |element| *element = engine);
+            config.modified = true;
+            Ok(line)
+        }
+        Operation::Remove { name, purge } => {
+            let engine = config.engines.named(&name).cloned().ok_or_else(|| io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("There is no engine named {} to remove", name),
+            ))?;
+            config.engines.remove(&engine.name);
+            Ok(format!("- {}", config.archive(engine, purge)))
+        }
+        Operation::Set { key, value } => {
+            keys::set_value(config, &key, value)?;
+            Ok(format!("= {}", key))
+        }
+    }
+}
+
+
+/// Applies the entries, a YAML or JSON list of engine definitions and operations, to the configuration, in order
+/// and all or nothing: if any of them fails, the configuration is left untouched. Returns the lines describing the
+/// changes.
+fn apply_all(config: &mut Configuration, content: &str) -> Result<Vec<String>, io::Error> {
+    let entries = match serde_yaml::from_str::<Value>(content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))? {
+        Value::Sequence(entries) => entries,
+        Value::Null => vec![],
+        entry => vec![entry],
+    };
+
+    let mut updated = config.clone();
+    let mut changes = vec![];
+    for (index, entry) in entries.into_iter().enumerate() {
+        let change = apply_entry(&mut updated, entry)
+            .map_err(|e| io::Error::new(e.kind(), format!("Entry {}: {}", index + 1, e)))?;
+        changes.push(change);
+    }
+
+    info!("Applied {} changes to the configuration", changes.len());
+    *config = updated;
+    Ok(changes)
+}
+
+
+/// Applies the entries read from the file, or from the standard input if the path is `-`, to the configuration, see
+/// [apply_all]
+pub fn run(config: &mut Configuration, source: &str) -> Result<Vec<String>, io::Error> {
+    apply_all(config, &read(source)?)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+
+    fn config() -> Configuration {
+        let mut duckduckgo = Engine::new("duckduckgo", "https://duckduckgo.com/?q={{term}}", "{{term}}", r"\s+", "+");
+        duckduckgo.aliases = vec!["ddg".to_string()];
+        Configuration::new(PathBuf::from("/nonexistent/search_config.yaml"), None, vec![duckduckgo])
+    }
+
+
+    #[test]
+    fn definitions_add_or_replace_engines() {
+        let mut config = config();
+        let changes = apply_all(&mut config, "
+- {name: duckduckgo, url_pattern: 'https://html.duckduckgo.com/?q={{term}}', pattern: '{{term}}', regex: ' ', replacement: '+'}
+- {name: ddg, url_pattern: 'https://ddg.test/?q={{term}}', pattern: '{{term}}', regex: ' ', replacement: '+'}
+").unwrap();
+        assert_eq!(changes, ["~ duckduckgo", "+ ddg"]);
+    }
+
+
+    #[test]
+    fn replace_needs_the_exact_name() {
+        let mut config = config();
+        let entry = "{op: replace, engine: {name: duck, url_pattern: 'x{{term}}', pattern: '{{term}}', regex: ' ', replacement: '+'}}";
+        let error = apply_all(&mut config, entry).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert_eq!(config.engines.named("duckduckgo").unwrap().url_pattern, "https://duckduckgo.com/?q={{term}}");
+
+        let entry = entry.replace("name: duck,", "name: DuckDuckGo,");
+        assert_eq!(apply_all(&mut config, &entry).unwrap(), ["~ duckduckgo"]);
+        assert_eq!(config.where_name("duckduckgo".to_string()).unwrap().name, "DuckDuckGo");
+    }
+
+
+    #[test]
+    fn remove_needs_the_exact_name() {
+        let mut config = config();
+        assert_eq!(apply_all(&mut config, "{op: remove, name: duck}").unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert_eq!(apply_all(&mut config, "{op: remove, name: ddg}").unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert!(config.contains("duckduckgo"));
+
+        assert_eq!(apply_all(&mut config, "{op: remove, name: DuckDuckGo}").unwrap(), ["- duckduckgo"]);
+        assert!(!config.contains("duckduckgo"));
+        assert_eq!(config.archived.len(), 1);
+    }
+
+
+    #[test]
+    fn entries_are_applied_all_or_nothing() {
+        let mut config = config();
+        let error = apply_all(&mut config, "
+- {op: remove, name: duckduckgo, purge: true}
+- {op: add, engine: {name: bad, url_pattern: 'x{{term}}', pattern: '{{term}}', regex: '(', replacement: '+'}}
+").unwrap_err();
+        assert!(error.to_string().starts_with("Entry 2:"));
+        assert!(config.contains("duckduckgo"));
+    }
+
+
+    #[test]
+    fn set_changes_the_settings() {
+        let mut config = config();
+        assert_eq!(apply_all(&mut config, "{op: set, key: settings.history, value: false}").unwrap(), ["= settings.history"]);
+        assert!(!config.settings.history);
+        assert!(apply_all(&mut config, "{op: set, key: settings.nonexistent, value: 1}").is_err());
+    }
+}
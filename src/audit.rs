@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use chrono::{DateTime, Local};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{print_json, Configuration};
+
+
+/// State of the configuration relevant to the audit, taken before a command runs so that it can be compared to
+/// the state after it
+#[derive(PartialEq)]
+pub struct Snapshot {
+    /// Name of the default engine
+    default: Option<String>,
+
+    /// Name and yaml representation of each engine, by uuid
+    engines: BTreeMap<Uuid, (String, String)>,
+}
+
+
+impl Snapshot {
+    /// Takes a snapshot of the configuration
+    pub fn of(config: &Configuration) -> Snapshot {
+        Snapshot {
+            default: config.default_engine.clone(),
+            engines: config.engines.iter()
+                .map(|engine| (engine.uuid, (engine.name.clone(), serde_yaml::to_string(engine).unwrap_or_default())))
+                .collect(),
+        }
+    }
+
+
+    /// Short description of the snapshot
+    fn summary(&self) -> String {
+        format!("default: {}; {} engines", self.default.as_deref().unwrap_or("none"), self.engines.len())
+    }
+
+
+    /// Describes the changes from this snapshot to the one passed as an argument, one per line: `+` for added
+    /// engines, `-` for removed ones, `~` for edited ones and the default engine change
+    fn changes(&self, after: &Snapshot) -> Vec<String> {
+        let mut changes = vec![];
+
+        for (uuid, (name, yaml)) in &after.engines {
+            match self.engines.get(uuid) {
+                None => changes.push(format!("+ {}", name)),
+                Some((_, previous)) if previous != yaml => changes.push(format!("~ {}", name)),
+                Some(_) => {}
+            }
+        }
+        for (uuid, (name, _)) in &self.engines {
+            if !after.engines.contains_key(uuid) {
+                changes.push(format!("- {}", name));
+            }
+        }
+        if self.default != after.default {
+            changes.push(format!(
+                "default: {} → {}",
+                self.default.as_deref().unwrap_or("none"),
+                after.default.as_deref().unwrap_or("none"),
+            ));
+        }
+
+        changes
+    }
+}
+
+
+/// Entry of the audit log, describing a mutation of the configuration
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Entry {
+    /// Moment in which the configuration was changed
+    timestamp: DateTime<Local>,
+
+    /// Command line arguments of the acting subcommand
+    command: String,
+
+    /// Summary of the configuration before the change
+    before: String,
+
+    /// Summary of the configuration after the change
+    after: String,
+
+    /// Changes made, as described by [Snapshot::changes]
+    changes: Vec<String>,
+}
+
+
+/// Appends an entry to the audit log, if the configuration changed from the snapshot `before`
+pub fn record(path: &Path, command: &str, before: &Snapshot, config: &Configuration) {
+    let after = Snapshot::of(config);
+    if *before == after {
+        return;
+    }
+
+    let entry = Entry {
+        timestamp: Local::now(),
+        command: command.to_string(),
+        before: before.summary(),
+        after: after.summary(),
+        changes: before.changes(&after),
+    };
+
+    let result = OpenOptions::new().create(true).append(true).open(path).and_then(|mut file| {
+        let line = serde_json::to_string(&entry).map_err(io::Error::other)?;
+        writeln!(file, "{}", line)
+    });
+
+    match result {
+        Ok(_) => info!("Change recorded in the audit log"),
+        Err(e) => error!("Unable to write to the audit log. Error: {}", e),
+    }
+}
+
+
+/// Reads every entry of the audit log, from the oldest to the latest
+pub fn entries(path: &Path) -> Vec<Entry> {
+    match File::open(path) {
+        Ok(file) => BufReader::new(file).lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+
+/// Prints the latest entries of the audit log, up to `limit` entries if one is given, as a JSON list with `json`
+pub fn show(path: &Path, limit: Option<usize>, json: bool) {
+    let entries = entries(path);
+    let skip = limit.map_or(0, |limit| entries.len().saturating_sub(limit));
+
+    if json {
+        print_json(&entries[skip..]);
+        return;
+    }
+
+    if entries.is_empty() {
+        println!("No changes recorded");
+    }
+
+    for entry in entries.iter().skip(skip) {
+        println!("{}  search {}", entry.timestamp.format("%Y-%m-%d %H:%M:%S"), entry.command);
+        println!("    {} → {}", entry.before, entry.after);
+        for change in &entry.changes {
+            println!("    {}", change);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+
+/// Number of backups kept in the backup directory, the oldest ones are removed first
+pub const KEEP: usize = 10;
+
+
+/// Lists the backups in the directory passed as an argument, from the oldest to the latest. The names carry the
+/// moment of the backup, so sorting them sorts the backups chronologically.
+pub fn list(directory: &Path) -> Vec<PathBuf> {
+    let mut backups: Vec<PathBuf> = match fs::read_dir(directory) {
+        Ok(entries) => entries.flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "yaml"))
+            .collect(),
+        Err(_) => vec![],
+    };
+    backups.sort();
+    backups
+}
+
+
+/// Returns the latest backup in the directory passed as an argument, if any
+pub fn latest(directory: &Path) -> Option<PathBuf> {
+    list(directory).pop()
+}
+
+
+/// Removes the oldest backups, keeping only the latest [KEEP] ones
+pub fn prune(directory: &Path) {
+    let backups = list(directory);
+    for path in backups.iter().take(backups.len().saturating_sub(KEEP)) {
+        match fs::remove_file(path) {
+            Ok(_) => info!("Removed the old backup {:?}", path),
+            Err(e) => warn!("Unable to remove the old backup {:?}. Error: {}", path, e),
+        }
+    }
+}
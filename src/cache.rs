@@ -0,0 +1,88 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use log::{info, warn};
+use sha2::{Digest, Sha256};
+
+
+/// Time during which expired responses are kept, to be used when the network can't be reached
+const STALE_LIMIT: Duration = Duration::from_secs(24 * 60 * 60);
+
+
+/// Responses of the network requests kept on disk for a short time, so that repeated keystrokes and repeated
+/// queries don't hit the network again. Each response is a file named after the hash of its key.
+pub struct Cache {
+    /// Directory holding the cached responses
+    directory: PathBuf,
+
+    /// Time during which a cached response is used, the cache being disabled if zero
+    ttl: Duration,
+}
+
+
+impl Cache {
+    pub fn new(directory: PathBuf, ttl: Duration) -> Cache {
+        Cache { directory, ttl }
+    }
+
+
+    /// Path of the file caching the response of the kind (such as `suggest`) for the engine and term
+    fn path(&self, kind: &str, engine: &str, term: &str) -> PathBuf {
+        let key = Sha256::digest(format!("{}\0{}\0{}", kind, engine, term.trim().to_lowercase()));
+        self.directory.join(format!("{:x}", key))
+    }
+
+
+    /// Whether the file was written within the time to live
+    fn fresh(&self, modified: SystemTime) -> bool {
+        modified.elapsed().is_ok_and(|age| age < self.ttl)
+    }
+
+
+    /// Returns the cached response, unless it's older than the time to live
+    pub fn get(&self, kind: &str, engine: &str, term: &str) -> Option<String> {
+        let path = self.path(kind, engine, term);
+        let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?;
+        if !self.fresh(modified) {
+            return None;
+        }
+
+        info!("Using the cached {} response of {} for {}", kind, engine, term);
+        fs::read_to_string(path).ok()
+    }
+
+
+    /// Returns the cached response, even if it's older than the time to live, for when the network can't be reached
+    pub fn get_stale(&self, kind: &str, engine: &str, term: &str) -> Option<String> {
+        fs::read_to_string(self.path(kind, engine, term)).ok()
+    }
+
+
+    /// Caches the response, removing the ones expired for too long along the way
+    pub fn put(&self, kind: &str, engine: &str, term: &str, response: &str) {
+        if self.ttl.is_zero() {
+            return;
+        }
+
+        if let Err(e) = fs::create_dir_all(&self.directory).and_then(|_| fs::write(self.path(kind, engine, term), response)) {
+            warn!("Unable to cache the {} response. Error: {}", kind, e);
+        }
+        if let Err(e) = self.prune() {
+            warn!("Unable to remove the expired responses from the cache. Error: {}", e);
+        }
+    }
+
+
+    /// Removes the responses expired for longer than [STALE_LIMIT]
+    fn prune(&self) -> Result<(), io::Error> {
+        for entry in fs::read_dir(&self.directory)? {
+            let entry = entry?;
+            if entry.metadata()?.modified()?.elapsed().is_ok_and(|age| age > self.ttl + STALE_LIMIT) {
+                fs::remove_file(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use log::info;
+
+use crate::headless;
+use crate::settings::Settings;
+
+
+/// Clipboard utilities tried, in order, with the arguments making them write the clipboard contents to the standard
+/// output
+const READERS: [(&str, &[&str]); 4] = [
+    ("wl-paste", &["--no-newline"]),
+    ("xclip", &["-selection", "clipboard", "-out"]),
+    ("xsel", &["--clipboard", "--output"]),
+    ("pbpaste", &[]),
+];
+
+
+/// Clipboard utilities tried, in order, with the arguments making them read the text from the standard input
+const WRITERS: [(&str, &[&str]); 4] = [
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("pbcopy", &[]),
+];
+
+
+/// Copies the text to the system clipboard, through the first clipboard utility found
+fn copy_locally(text: &str) -> Result<(), io::Error> {
+    let (program, arguments) = WRITERS.iter()
+        .find(|(program, _)| which::which(program).is_ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No clipboard utility found (wl-copy, xclip, xsel or pbcopy)"))?;
+
+    info!("Copying to the clipboard with {}", program);
+    let mut child = Command::new(program).args(*arguments).stdin(Stdio::piped()).spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{} exited with {}", program, status)))
+    }
+}
+
+
+/// Whether the program runs inside an SSH session
+fn remote() -> bool {
+    ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"].iter().any(|variable| std::env::var_os(variable).is_some())
+}
+
+
+/// Copies the text to the clipboard of the machine running the terminal emulator through an OSC 52 escape sequence,
+/// written to the terminal. Inside tmux the sequence is wrapped so that tmux passes it through. Texts encoding to
+/// more than `max_bytes` are refused, since terminals silently drop the sequences over their own limit.
+fn copy_through_terminal(text: &str, max_bytes: usize) -> Result<(), io::Error> {
+    let encoded = STANDARD.encode(text);
+    if encoded.len() > max_bytes {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+            format!("The text is too long to be copied through the terminal ({} bytes encoded, at most {})", encoded.len(), max_bytes)));
+    }
+
+    let sequence = format!("\x1b]52;c;{}\x07", encoded);
+    let sequence = if std::env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    };
+
+    info!("Copying to the clipboard through an OSC 52 sequence");
+    match OpenOptions::new().write(true).open("/dev/tty") {
+        Ok(mut terminal) => terminal.write_all(sequence.as_bytes()),
+        Err(_) => io::stdout().write_all(sequence.as_bytes()),
+    }
+}
+
+
+/// Copies the text to the clipboard. Inside an SSH session without a graphical session or a clipboard utility, the
+/// text is copied to the clipboard of the local machine through the terminal emulator instead, unless `osc52` is
+/// turned off in the settings.
+pub fn copy(text: &str, settings: &Settings) -> Result<(), io::Error> {
+    if settings.osc52 && remote() && headless() {
+        return copy_through_terminal(text, settings.osc52_max_bytes);
+    }
+
+    match copy_locally(text) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound && settings.osc52 && remote() => {
+            copy_through_terminal(text, settings.osc52_max_bytes)
+        }
+        result => result,
+    }
+}
+
+
+/// Returns the contents of the system clipboard, through the first clipboard utility found
+pub fn paste() -> Result<String, io::Error> {
+    let (program, arguments) = READERS.iter()
+        .find(|(program, _)| which::which(program).is_ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No clipboard utility found (wl-paste, xclip, xsel or pbpaste)"))?;
+
+    info!("Reading the clipboard with {}", program);
+    let output = Command::new(program).args(*arguments).output()?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(io::Error::other(format!("{} exited with {}", program, output.status)))
+    }
+}
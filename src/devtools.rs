@@ -0,0 +1,62 @@
+use std::io;
+use std::time::Duration;
+
+use log::info;
+use serde::Deserialize;
+use serde_json::json;
+use tungstenite::Message;
+
+/// Endpoint used when none is configured, the default remote debugging port of Chromium
+pub const DEFAULT_ENDPOINT: &str = "http://127.0.0.1:9222";
+
+
+/// Target (tab, worker, extension...) listed by the remote debugging endpoint
+#[derive(Deserialize, Debug)]
+struct Target {
+    /// Identifier of the target
+    id: String,
+
+    /// Websocket through which the target is controlled
+    #[serde(rename = "webSocketDebuggerUrl")]
+    web_socket_debugger_url: Option<String>,
+}
+
+
+/// Requests the endpoint, reading the answer as JSON
+fn request<T: for<'de> Deserialize<'de>>(request: ureq::Request) -> Result<T, io::Error> {
+    let response = request.call().map_err(io::Error::other)?;
+    serde_json::from_reader(response.into_reader()).map_err(io::Error::other)
+}
+
+
+/// Navigates the target to the URL through its websocket, with the `Page.navigate` command
+fn navigate(web_socket: &str, url: &str) -> Result<(), io::Error> {
+    let (mut socket, _) = tungstenite::connect(web_socket).map_err(io::Error::other)?;
+    let command = json!({"id": 1, "method": "Page.navigate", "params": {"url": url}});
+    socket.send(Message::Text(command.to_string())).map_err(io::Error::other)?;
+    socket.read().map_err(io::Error::other)?;
+    socket.close(None).map_err(io::Error::other)
+}
+
+
+/// Opens the URL in the tab whose identifier is passed as an argument, through the remote debugging endpoint of
+/// a running browser, bringing the tab to the front. If the tab no longer exists, a new one is created. Returns
+/// the identifier of the tab used, to be reused by the next search.
+pub fn open(endpoint: &str, url: &str, tab: Option<&str>) -> Result<String, io::Error> {
+    let endpoint = endpoint.trim_end_matches('/');
+    let agent = ureq::AgentBuilder::new().timeout(Duration::from_secs(2)).build();
+
+    let targets: Vec<Target> = request(agent.get(&format!("{}/json/list", endpoint)))?;
+    let existing = tab.and_then(|id| targets.into_iter().find(|target| target.id == id));
+
+    if let Some(Target { id, web_socket_debugger_url: Some(web_socket) }) = existing {
+        info!("Reusing the tab {}", id);
+        navigate(&web_socket, url)?;
+        agent.get(&format!("{}/json/activate/{}", endpoint, id)).call().map_err(io::Error::other)?;
+        return Ok(id);
+    }
+
+    info!("Opening a new tab to be reused");
+    let target: Target = request(agent.put(&format!("{}/json/new?{}", endpoint, url)))?;
+    Ok(target.id)
+}
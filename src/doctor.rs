@@ -0,0 +1,214 @@
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{http, progress, Configuration, Engine, EngineKind};
+use crate::reload::ConfigWatcher;
+
+/// Sample term used to generate the URL requested from each engine during the online checks.
+const SAMPLE_QUERY: &str = "search";
+
+/// Result of the health check of a single search engine, stored in the health file so that the last
+/// known state of every engine can be reported by `doctor` without touching the network.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HealthRecord {
+    /// Name of the checked search engine
+    engine: String,
+
+    /// HTTP status returned by the engine host, if a response was received
+    status: Option<u16>,
+
+    /// Time, in milliseconds, spent on the request
+    latency_ms: u64,
+
+    /// Moment in which the check was performed
+    checked_at: DateTime<Local>,
+
+    /// Description of the problem found, null when the engine is healthy
+    problem: Option<String>,
+}
+
+
+impl HealthRecord {
+    /// Requests the engine host with [SAMPLE_QUERY] and records the status and latency of the response.
+    /// Engines answering with 404 or stuck in a redirect loop are flagged.
+    pub fn check(agent: &ureq::Agent, engine: &Engine) -> HealthRecord {
+        info!("Checking the health of the engine {}", engine.name);
+        let checked_at = Local::now();
+
+        let url = match engine.url(SAMPLE_QUERY) {
+            Ok(url) => url,
+            Err(e) => return HealthRecord::unchecked(engine, format!("Unable to generate URL: {}", e)),
+        };
+
+        let start = Instant::now();
+        let result = http::get(agent, engine, &url).call();
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        let (status, problem) = match result {
+            Ok(response) => (Some(response.status()), None),
+            Err(ureq::Error::Status(404, _)) => (Some(404), Some(String::from("Not found (404)"))),
+            Err(ureq::Error::Status(code, _)) => (Some(code), Some(format!("Host answered with status {}", code))),
+            Err(ureq::Error::Transport(transport)) => {
+                if transport.kind() == ureq::ErrorKind::TooManyRedirects {
+                    (None, Some(String::from("Redirect loop")))
+                } else {
+                    (None, Some(format!("Request failed: {}", transport)))
+                }
+            }
+        };
+
+        if let Some(problem) = &problem {
+            warn!("Engine {} is unhealthy: {}", engine.name, problem);
+        }
+
+        HealthRecord {
+            engine: engine.name.clone(),
+            status,
+            latency_ms,
+            checked_at,
+            problem,
+        }
+    }
+
+
+    /// Records the problem that kept the engine from being requested
+    pub fn unchecked(engine: &Engine, problem: String) -> HealthRecord {
+        warn!("Engine {} could not be checked: {}", engine.name, problem);
+        HealthRecord {
+            engine: engine.name.clone(),
+            status: None,
+            latency_ms: 0,
+            checked_at: Local::now(),
+            problem: Some(problem),
+        }
+    }
+
+
+    /// Prints the record as a single line of the report
+    pub fn print(&self) {
+        let status = self.status.map_or(String::from("---"), |status| status.to_string());
+        match &self.problem {
+            Some(problem) => println!("✘ {:<20} {:>4} {:>6} ms  {}", self.engine, status, self.latency_ms, problem),
+            None => println!("✔ {:<20} {:>4} {:>6} ms", self.engine, status, self.latency_ms),
+        }
+    }
+}
+
+
+/// Loads the records of the last online check, an empty list is returned if no check was performed yet.
+pub fn load_records(path: &Path) -> Vec<HealthRecord> {
+    match File::open(path) {
+        Ok(file) => serde_yaml::from_reader(file).unwrap_or_else(|e| {
+            error!("Unable to read the health file. Error: {}", e);
+            vec![]
+        }),
+        Err(_) => vec![],
+    }
+}
+
+
+/// Saves the records of an online check, replacing the previous ones
+pub fn save_records(path: &Path, records: &[HealthRecord]) -> Result<(), io::Error> {
+    let file = File::create(path)?;
+    serde_yaml::to_writer(file, records).map_err(io::Error::other)
+}
+
+
+/// Checks every configured web engine, returning one record per engine. The engines are requested through Tor
+/// when `tor` is given or they have `via_tor`.
+fn check_all(config: &Configuration, tor: bool) -> Vec<HealthRecord> {
+    let engines: Vec<&Engine> = config.engines.iter().filter(|engine| engine.kind == EngineKind::Web).collect();
+    let progress = progress::bar(engines.len() as u64);
+
+    let records = engines.into_iter()
+        .map(|engine| {
+            progress.set_message(engine.name.clone());
+            let record = match http::agent(&config.settings.http.for_engine(engine, tor)) {
+                Ok(agent) => HealthRecord::check(&agent.redirects(10).build(), engine),
+                Err(e) => HealthRecord::unchecked(engine, e.to_string()),
+            };
+            progress.inc(1);
+            record
+        })
+        .collect();
+    progress.finish_and_clear();
+    records
+}
+
+
+/// Prints the records as a single JSON object, along with the name of the default engine, null if there's none
+fn print_json(config: &Configuration, records: &[HealthRecord]) {
+    crate::print_json(&json!({
+        "default_engine": config.default().map(|engine| engine.name),
+        "records": records,
+    }));
+}
+
+
+/// Performs one online check, stores it in the health file and prints the report, as JSON with `json`
+fn check_and_report(config: &Configuration, health_path: &Path, tor: bool, json: bool) {
+    let records = check_all(config, tor);
+    if let Err(e) = save_records(health_path, &records) {
+        error!("Unable to save the health file. Error: {}", e);
+    }
+    if json {
+        print_json(config, &records);
+        return;
+    }
+    for record in &records {
+        record.print();
+    }
+}
+
+
+/// Entry point of the `doctor` subcommand. Without `online` the records of the last check are reported,
+/// with it the engines are checked right away and, if an `interval` is given, periodically until interrupted.
+/// While checking periodically, changes to the configuration file are picked up before each check. With `json`
+/// every report is printed as a JSON object, one per line.
+pub fn run(config: &mut Configuration, health_path: PathBuf, online: bool, interval: Option<u64>, tor: bool, json: bool) {
+    if !json && config.default().is_none() {
+        println!("✘ No default search engine defined");
+    }
+
+    if !online {
+        let records = load_records(&health_path);
+        if json {
+            print_json(config, &records);
+            return;
+        }
+        match records.first() {
+            Some(first) => {
+                println!("Last online check: {}", first.checked_at.format("%Y-%m-%d %H:%M:%S"));
+                for record in &records {
+                    record.print();
+                }
+            }
+            None => println!("No online check was performed yet, run `search doctor --online`"),
+        }
+        return;
+    }
+
+    match interval {
+        Some(seconds) => {
+            let watcher = ConfigWatcher::new(&config.file_path)
+                .inspect_err(|e| warn!("Unable to watch the configuration file. Error: {}", e))
+                .ok();
+            loop {
+                if let Some(watcher) = &watcher {
+                    watcher.reload_if_changed(config);
+                }
+                check_and_report(config, &health_path, tor, json);
+                sleep(Duration::from_secs(seconds));
+            }
+        }
+        None => check_and_report(config, &health_path, tor, json),
+    }
+}
@@ -0,0 +1,54 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use log::info;
+
+
+/// Terminal emulators whose command line interface can open URLs on behalf of the program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emulator {
+    /// kitty, through its remote control (`kitty @`), which must be enabled with `allow_remote_control`
+    Kitty,
+}
+
+
+impl Emulator {
+    /// The terminal emulator the program is running inside, if its command line interface is installed
+    pub fn detect() -> Option<Emulator> {
+        if std::env::var_os("KITTY_WINDOW_ID").is_some() && which::which("kitty").is_ok() {
+            Some(Emulator::Kitty)
+        } else {
+            None
+        }
+    }
+
+
+    /// Opens the URL through the URL-opening facilities of the terminal emulator, which apply its own rules and
+    /// work from SSH sessions as well
+    pub fn open(self, url: &str) -> Result<(), io::Error> {
+        match self {
+            Emulator::Kitty => run(Command::new("kitty").args(["@", "action", "open_url", url])),
+        }
+    }
+
+
+    /// Opens the URL with the terminal browser in a new pane, split from the current one
+    pub fn split(self, browser: &Path, url: &str) -> Result<(), io::Error> {
+        info!("Opening {} with {:?} in a new pane", url, browser);
+        match self {
+            Emulator::Kitty => run(Command::new("kitty").args(["@", "launch", "--type=window", "--location=vsplit", "--cwd=current"]).arg(browser).arg(url)),
+        }
+    }
+}
+
+
+/// Runs the command, failing if it exits unsuccessfully, as when the remote control of kitty is disabled
+fn run(command: &mut Command) -> Result<(), io::Error> {
+    let output = command.output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()))
+    }
+}
@@ -0,0 +1,140 @@
+use std::io;
+
+use log::info;
+use serde::{Deserialize, Serialize};
+
+
+/// Characters left as they are in every part of the URL, the unreserved ones of RFC 3986
+fn unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')
+}
+
+
+/// Characters left as they are in a path segment besides the unreserved ones, the sub-delimiters, `:` and `@`
+fn segment_safe(byte: u8) -> bool {
+    matches!(byte, b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'=' | b':' | b'@')
+}
+
+
+/// How the spaces of the term are written in the URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Space {
+    /// As `+`, the way HTML forms send them in query strings
+    #[serde(rename = "+")]
+    Plus,
+
+    /// As `%20`
+    #[serde(rename = "%20")]
+    Percent,
+}
+
+
+/// Part of the URL the term is placed in, each with its own encoding rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Component {
+    /// A value of the query string, as in `?q={{term}}`, in which every reserved character is encoded
+    #[default]
+    Query,
+
+    /// A segment of the path, as in `/wiki/{{term}}`, in which `/`, `?` and `#` are encoded so that the term stays in
+    /// a single segment, while the sub-delimiters such as `+`, `:` and `@` are left as they are
+    Path,
+}
+
+
+/// How the term is percent-encoded once the regex of the engine is applied. Engines without one receive the term as
+/// the regex leaves it, for the browser to encode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Encoding {
+    /// How the spaces are written, `+` in query strings and `%20` in paths if null. Paths always take `%20`, since a
+    /// `+` in a path is a plus sign.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub space: Option<Space>,
+
+    /// Charset the term is converted to before being percent-encoded, by its WHATWG label, such as `shift_jis` or
+    /// `iso-8859-1`. Characters the charset lacks are written as HTML numeric references, as browsers do.
+    pub charset: String,
+
+    /// Part of the URL the term is placed in
+    pub component: Component,
+}
+
+
+impl Default for Encoding {
+    fn default() -> Encoding {
+        Encoding { space: None, charset: "utf-8".to_string(), component: Component::Query }
+    }
+}
+
+
+impl Encoding {
+    /// Charset of the encoding, failing if the label is unknown
+    pub fn charset(&self) -> Result<&'static encoding_rs::Encoding, io::Error> {
+        encoding_rs::Encoding::for_label(self.charset.trim().as_bytes()).ok_or_else(|| io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unknown charset {}, expected a label such as utf-8, shift_jis or iso-8859-1", self.charset),
+        ))
+    }
+
+
+    /// Percent-encodes the term in the charset, following the rules of the component, as in `東京 駅` turning into
+    /// `%93%8C%8B%9E+%89w` in Shift-JIS
+    pub fn encode(&self, term: &str) -> Result<String, io::Error> {
+        let charset = self.charset()?;
+        let (bytes, _, unmappable) = charset.encode(term);
+        if unmappable {
+            info!("Some characters of the term are missing from {}, written as numeric references", charset.name());
+        }
+
+        let space = match (self.component, self.space) {
+            (Component::Query, Some(Space::Plus) | None) => "+",
+            _ => "%20",
+        };
+        let encoded = bytes.iter()
+            .map(|&byte| match byte {
+                b' ' => space.to_string(),
+                _ if unreserved(byte) => (byte as char).to_string(),
+                _ if self.component == Component::Path && segment_safe(byte) => (byte as char).to_string(),
+                _ => format!("%{:02X}", byte),
+            })
+            .collect();
+        Ok(encoded)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    fn encoding(charset: &str, component: Component, space: Option<Space>) -> Encoding {
+        Encoding { space, charset: charset.to_string(), component }
+    }
+
+
+    #[test]
+    fn query_values_are_encoded_in_the_charset() {
+        assert_eq!(encoding("shift_jis", Component::Query, None).encode("東京 駅").unwrap(), "%93%8C%8B%9E+%89w");
+        assert_eq!(encoding("utf-8", Component::Query, None).encode("a+b/c é").unwrap(), "a%2Bb%2Fc+%C3%A9");
+        assert_eq!(encoding("utf-8", Component::Query, Some(Space::Percent)).encode("a b").unwrap(), "a%20b");
+    }
+
+
+    #[test]
+    fn path_segments_keep_sub_delimiters_and_encode_slashes() {
+        let path = encoding("utf-8", Component::Path, Some(Space::Plus));
+        assert_eq!(path.encode("C++ a/b?").unwrap(), "C++%20a%2Fb%3F");
+        assert_eq!(path.encode("user@host:1").unwrap(), "user@host:1");
+    }
+
+
+    #[test]
+    fn unknown_charsets_are_rejected() {
+        let error = encoding("klingon", Component::Query, None).encode("term").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(encoding(" Latin1 ", Component::Query, None).charset().is_ok());
+    }
+}
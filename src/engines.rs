@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use indexmap::IndexMap;
+use log::warn;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+
+use crate::{normalize_name, Engine};
+
+
+/// Configured search engines, keyed by normalized name in the order they were added. An index from every word an
+/// engine can be referred to by (its name, aliases and bang keyword) to its key makes the lookups constant-time.
+///
+/// The engines are still written to the configuration file as a list, but a map from name to engine is accepted
+/// when reading it as well.
+#[derive(Default, Clone)]
+pub struct Engines {
+    /// Engines by normalized name
+    map: IndexMap<String, Engine>,
+
+    /// Key in [Self::map] of the engine referred to by each normalized name, alias and bang keyword. Names take
+    /// precedence over aliases and bangs, which refer to the first engine declaring them.
+    index: HashMap<String, String>,
+}
+
+
+impl Engines {
+    /// Rebuilds the index after the engines changed
+    fn reindex(&mut self) {
+        self.index = self.map.keys().map(|key| (key.clone(), key.clone())).collect();
+        for (key, engine) in &self.map {
+            for keyword in engine.aliases.iter().chain(engine.bang.iter()) {
+                self.index.entry(normalize_name(keyword)).or_insert_with(|| key.clone());
+            }
+        }
+    }
+
+
+    /// Adds the engine, replacing the one configured under the same name, which is returned. A new engine, placed
+    /// last, is only added to the index, so that adding thousands of engines, as when importing them, doesn't
+    /// rebuild it every time.
+    pub fn insert(&mut self, engine: Engine) -> Option<Engine> {
+        let key = normalize_name(&engine.name);
+        let keywords: Vec<String> = engine.aliases.iter().chain(engine.bang.iter()).map(|keyword| normalize_name(keyword)).collect();
+
+        let previous = self.map.insert(key.clone(), engine);
+        if previous.is_some() {
+            self.reindex();
+        } else {
+            self.index.insert(key.clone(), key.clone());
+            for keyword in keywords {
+                self.index.entry(keyword).or_insert_with(|| key.clone());
+            }
+        }
+        previous
+    }
+
+
+    /// Returns the engine referred to by the name, alias or bang keyword passed as an argument
+    pub fn get(&self, name: &str) -> Option<&Engine> {
+        self.index.get(&normalize_name(name)).and_then(|key| self.map.get(key))
+    }
+
+
+    /// Returns the engine configured under the name passed as an argument, ignoring aliases and bang keywords
+    pub fn named(&self, name: &str) -> Option<&Engine> {
+        self.map.get(&normalize_name(name))
+    }
+
+
+    /// Applies the change to the engine referred to by the name passed as an argument, returning the result of the
+    /// change, or `None` if there's no such engine
+    pub fn modify<T>(&mut self, name: &str, change: impl FnOnce(&mut Engine) -> T) -> Option<T> {
+        let key = self.index.get(&normalize_name(name))?.clone();
+        let result = self.map.get_mut(&key).map(change);
+        self.reindex();
+        result
+    }
+
+
+    /// Removes the engine referred to by the name passed as an argument, returning it
+    pub fn remove(&mut self, name: &str) -> Option<Engine> {
+        let key = self.index.get(&normalize_name(name))?.clone();
+        let removed = self.map.shift_remove(&key);
+        self.reindex();
+        removed
+    }
+
+
+    /// Keeps only the engines for which the predicate holds
+    pub fn retain(&mut self, mut keep: impl FnMut(&Engine) -> bool) {
+        self.map.retain(|_, engine| keep(engine));
+        self.reindex();
+    }
+
+
+    /// Iterates over the engines, in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = &Engine> {
+        self.map.values()
+    }
+
+
+    /// Whether no engine is configured
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+
+impl FromIterator<Engine> for Engines {
+    /// Collects the engines. When several share a name, as the `add --force` of older releases allowed, the later
+    /// ones are renamed with a numeric suffix, `ddg-2`, rather than lost on the next save.
+    fn from_iter<I: IntoIterator<Item = Engine>>(engines: I) -> Engines {
+        let mut map = IndexMap::new();
+        for mut engine in engines {
+            if map.contains_key(&normalize_name(&engine.name)) {
+                let name = (2..)
+                    .map(|suffix| format!("{}-{}", engine.name, suffix))
+                    .find(|name| !map.contains_key(&normalize_name(name)))
+                    .unwrap();
+                warn!("Renaming the engine {} to {}, its name is repeated", engine.name, name);
+                eprintln!("The engine name {} is repeated, the repeated engine was renamed to {}", engine.name, name);
+                engine.name = name;
+            }
+            map.insert(normalize_name(&engine.name), engine);
+        }
+
+        let mut engines = Engines { map, index: HashMap::new() };
+        engines.reindex();
+        engines
+    }
+}
+
+
+impl fmt::Debug for Engines {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.map.values()).finish()
+    }
+}
+
+
+impl Serialize for Engines {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.map.values())
+    }
+}
+
+
+/// Reads the engines either as a list or as a map from name to engine, an empty or null value meaning no engines
+struct EnginesVisitor;
+
+
+impl<'de> Visitor<'de> for EnginesVisitor {
+    type Value = Engines;
+
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of engines or a map from name to engine")
+    }
+
+
+    fn visit_unit<E: de::Error>(self) -> Result<Engines, E> {
+        Ok(Engines::default())
+    }
+
+
+    fn visit_none<E: de::Error>(self) -> Result<Engines, E> {
+        Ok(Engines::default())
+    }
+
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Engines, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Engines, A::Error> {
+        let mut engines = vec![];
+        while let Some(engine) = seq.next_element::<Engine>()? {
+            engines.push(engine);
+        }
+        Ok(engines.into_iter().collect())
+    }
+
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Engines, A::Error> {
+        let mut engines = vec![];
+        while let Some((_, engine)) = map.next_entry::<String, Engine>()? {
+            engines.push(engine);
+        }
+        Ok(engines.into_iter().collect())
+    }
+}
+
+
+impl<'de> Deserialize<'de> for Engines {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Engines, D::Error> {
+        deserializer.deserialize_option(EnginesVisitor)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    fn engine(name: &str) -> Engine {
+        Engine::new(name, &format!("https://{}.test/?q={{{{term}}}}", name), "{{term}}", r"\s+", "+")
+    }
+
+
+    #[test]
+    fn repeated_names_are_renamed_rather_than_dropped() {
+        let engines: Engines = [engine("ddg"), engine("DDG "), engine("ddg")].into_iter().collect();
+        let names: Vec<&str> = engines.iter().map(|engine| engine.name.as_str()).collect();
+        assert_eq!(names, ["ddg", "DDG-2", "ddg-3"]);
+    }
+
+
+    #[test]
+    fn lookups_follow_names_aliases_and_bangs() {
+        let mut google = engine("google");
+        google.aliases = vec!["goo".to_string()];
+        google.bang = Some("g".to_string());
+        let mut engines: Engines = [engine("ddg"), google].into_iter().collect();
+
+        assert_eq!(engines.get(" Google").unwrap().name, "google");
+        assert_eq!(engines.get("goo").unwrap().name, "google");
+        assert_eq!(engines.get("g").unwrap().name, "google");
+        assert!(engines.get("goog").is_none());
+        assert!(engines.named("goo").is_none());
+        assert_eq!(engines.named("GOOGLE").unwrap().name, "google");
+
+        engines.modify("google", |engine| engine.aliases = vec!["gg".to_string()]);
+        assert!(engines.get("goo").is_none());
+        assert_eq!(engines.get("gg").unwrap().name, "google");
+
+        engines.remove("gg");
+        assert!(engines.get("google").is_none());
+        assert!(engines.get("g").is_none());
+    }
+
+
+    #[test]
+    fn insert_indexes_new_engines_and_replaces_existing_ones() {
+        let mut engines = Engines::default();
+        let mut first = engine("wiki");
+        first.bang = Some("w".to_string());
+        assert!(engines.insert(first).is_none());
+        assert_eq!(engines.get("w").unwrap().name, "wiki");
+
+        let replaced = engines.insert(engine("Wiki"));
+        assert_eq!(replaced.unwrap().name, "wiki");
+        assert_eq!(engines.get("wiki").unwrap().name, "Wiki");
+        assert!(engines.get("w").is_none());
+    }
+
+
+    #[test]
+    fn retain_drops_the_keywords_of_the_removed_engines() {
+        let mut bing = engine("bing");
+        bing.bang = Some("b".to_string());
+        let mut engines: Engines = [engine("ddg"), bing, engine("wiki")].into_iter().collect();
+
+        engines.retain(|engine| engine.name != "bing");
+        assert!(engines.get("b").is_none());
+        assert_eq!(engines.iter().map(|engine| engine.name.as_str()).collect::<Vec<_>>(), ["ddg", "wiki"]);
+    }
+
+
+    #[test]
+    fn engines_are_read_from_a_list_or_a_map() {
+        let list: Engines = serde_yaml::from_str("- {name: a, url_pattern: 'x{{term}}', pattern: '{{term}}', regex: ' ', replacement: '+'}").unwrap();
+        let map: Engines = serde_yaml::from_str("a: {name: a, url_pattern: 'x{{term}}', pattern: '{{term}}', regex: ' ', replacement: '+'}").unwrap();
+        let empty: Engines = serde_yaml::from_str("null").unwrap();
+        assert!(list.get("a").is_some());
+        assert!(map.get("a").is_some());
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,268 @@
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use log::{info, warn};
+use rayon::prelude::*;
+
+use crate::{http, progress, Configuration, Engine, EngineKind};
+
+
+/// Signature starting every PNG image
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+
+/// Largest favicon downloaded, larger responses being cut
+const MAX_BYTES: u64 = 256 * 1024;
+
+
+/// Largest chunk of an image sent in a single escape sequence of the kitty graphics protocol
+const CHUNK: usize = 4096;
+
+
+/// How the favicons are drawn in the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// As images, with the graphics protocol of kitty, also understood by WezTerm and Ghostty
+    Kitty,
+
+    /// As the first letter of the engine name in a circle, when images can't be shown
+    Unicode,
+}
+
+
+impl Protocol {
+    /// Protocol understood by the terminal the standard output is written to. Pagers drop the escape sequences of
+    /// images, so the letters are used when paging.
+    pub fn detect(pager: bool) -> Protocol {
+        let variable = |name: &str| std::env::var(name).unwrap_or_default();
+        let kitty = std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || matches!(variable("TERM").as_str(), "xterm-kitty" | "xterm-ghostty")
+            || matches!(variable("TERM_PROGRAM").as_str(), "WezTerm" | "ghostty");
+
+        if kitty && !pager && io::stdout().is_terminal() {
+            Protocol::Kitty
+        } else {
+            Protocol::Unicode
+        }
+    }
+}
+
+
+/// Directory holding the cached favicons, one file per host
+pub fn directory(search_dir: &Path) -> PathBuf {
+    search_dir.join("favicons")
+}
+
+
+/// Scheme and host of the home of the engine, as `https://duckduckgo.com`, the favicon being at its root
+fn origin(engine: &Engine) -> Option<String> {
+    let home = engine.home();
+    let (scheme, rest) = home.split_once("://")?;
+    let host = rest.split(['/', '?', '#']).next()?.to_lowercase();
+    let valid = !host.is_empty() && host.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':'));
+    valid.then(|| format!("{}://{}", scheme, host))
+}
+
+
+/// File caching the favicon of the engine, named after its host
+fn path(directory: &Path, engine: &Engine) -> Option<PathBuf> {
+    let origin = origin(engine)?;
+    let host = origin.split_once("://")?.1;
+    Some(directory.join(host.replace(':', "_")))
+}
+
+
+/// Whether the favicon of the engine was requested already, whether or not the engine has one
+fn cached(directory: &Path, engine: &Engine) -> bool {
+    path(directory, engine).is_some_and(|path| path.exists())
+}
+
+
+/// Web engines whose favicon was never requested
+pub fn missing<'a>(directory: &Path, engines: impl Iterator<Item = &'a Engine>) -> Vec<&'a Engine> {
+    engines.filter(|engine| engine.kind == EngineKind::Web && origin(engine).is_some() && !cached(directory, engine)).collect()
+}
+
+
+/// PNG image of the favicon: the favicon itself if it's a PNG file, otherwise the largest PNG image of the ICO file,
+/// the format modern sites store the images of their ICO files in. Favicons without one, such as the ones holding
+/// bitmaps only, have no image.
+fn png(data: &[u8]) -> Option<&[u8]> {
+    if data.starts_with(PNG_SIGNATURE) {
+        return Some(data);
+    }
+    if data.len() < 6 || data[..4] != [0, 0, 1, 0] {
+        return None;
+    }
+
+    let count = u16::from_le_bytes([data[4], data[5]]) as usize;
+    (0..count)
+        .filter_map(|index| {
+            let entry = data.get(6 + index * 16..6 + (index + 1) * 16)?;
+            let size = u32::from_le_bytes(entry[8..12].try_into().ok()?) as usize;
+            let offset = u32::from_le_bytes(entry[12..16].try_into().ok()?) as usize;
+            let image = data.get(offset..offset.checked_add(size)?)?;
+            image.starts_with(PNG_SIGNATURE).then_some(image)
+        })
+        .max_by_key(|image| image.len())
+}
+
+
+/// Downloads the favicon of the engine, at the root of its home, into the directory. An empty file is written when
+/// the engine has none, so that it isn't requested again.
+fn fetch(directory: &Path, engine: &Engine, settings: &http::HttpSettings) -> Result<(), io::Error> {
+    let (origin, path) = origin(engine).zip(path(directory, engine))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("The engine {} has no host", engine.name)))?;
+
+    let agent = http::agent(settings)?.build();
+    let mut body = vec![];
+    match http::get(&agent, engine, &format!("{}/favicon.ico", origin)).call() {
+        Ok(response) => {
+            response.into_reader().take(MAX_BYTES).read_to_end(&mut body)?;
+        }
+        Err(ureq::Error::Status(status, _)) => info!("{} has no favicon, status {}", engine.name, status),
+        Err(e) => return Err(io::Error::other(e)),
+    }
+
+    fs::create_dir_all(directory)?;
+    fs::write(path, body)
+}
+
+
+/// Entry point of the `favicons fetch` subcommand. Downloads, in parallel, the favicons of the web engines never
+/// requested or, with `refresh`, of every one of them. The engines are requested through Tor when `tor` is given or
+/// they have `via_tor`.
+pub fn fetch_all(config: &Configuration, directory: &Path, refresh: bool, tor: bool) {
+    let engines = if refresh {
+        config.engines.iter().filter(|engine| engine.kind == EngineKind::Web).collect()
+    } else {
+        missing(directory, config.engines.iter())
+    };
+    let progress = progress::bar(engines.len() as u64);
+
+    engines.into_par_iter().for_each(|engine| {
+        if let Err(e) = fetch(directory, engine, &config.settings.http.for_engine(engine, tor)) {
+            warn!("Unable to fetch the favicon of {}. Error: {}", engine.name, e);
+        }
+        progress.inc(1);
+    });
+    progress.finish_and_clear();
+}
+
+
+/// Runs `favicons fetch` in the background, detached from the terminal, so that the listing isn't held up by the
+/// network and shows the favicons from the next time on
+pub fn fetch_in_background(tor: bool) {
+    let spawned = std::env::current_exe().and_then(|program| {
+        Command::new(program)
+            .args(["favicons", "fetch", "--plain"])
+            .args(tor.then_some("--tor"))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+    });
+    if let Err(e) = spawned {
+        warn!("Unable to fetch the favicons in the background. Error: {}", e);
+    }
+}
+
+
+/// First letter or digit of the engine name in a circle, `Ⓖ` for google, or a dotted circle if it has neither
+fn letter(engine: &Engine) -> char {
+    let circled = |c: char| match c {
+        'A'..='Z' => char::from_u32(0x24B6 + (c as u32 - 'A' as u32)),
+        '1'..='9' => char::from_u32(0x2460 + (c as u32 - '1' as u32)),
+        '0' => Some('⓪'),
+        _ => None,
+    };
+    engine.name.chars()
+        .find(|c| c.is_ascii_alphanumeric())
+        .and_then(|c| circled(c.to_ascii_uppercase()))
+        .unwrap_or('◌')
+}
+
+
+/// Escape sequences drawing the image over two columns of the current line with the kitty graphics protocol, in
+/// chunks as the protocol asks for
+fn kitty(image: &[u8]) -> String {
+    let encoded = STANDARD.encode(image);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(CHUNK).collect();
+
+    chunks.iter().enumerate()
+        .map(|(index, chunk)| {
+            let more = u8::from(index + 1 < chunks.len());
+            let chunk = String::from_utf8_lossy(chunk);
+            if index == 0 {
+                format!("\x1b_Gf=100,a=T,c=2,r=1,q=2,m={};{}\x1b\\", more, chunk)
+            } else {
+                format!("\x1b_Gm={};{}\x1b\\", more, chunk)
+            }
+        })
+        .collect()
+}
+
+
+/// Icon of the engine, two columns wide: its cached favicon, with the kitty protocol, or else its circled letter
+pub fn icon(directory: &Path, engine: &Engine, protocol: Protocol) -> String {
+    if protocol == Protocol::Kitty {
+        let data = path(directory, engine).and_then(|path| fs::read(path).ok()).unwrap_or_default();
+        if let Some(image) = png(&data) {
+            return kitty(image);
+        }
+    }
+    format!("{} ", letter(engine))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// ICO file holding a bitmap and, second, the PNG image passed as an argument
+    fn ico(image: &[u8]) -> Vec<u8> {
+        let bitmap = [0u8; 8];
+        let mut data = vec![0, 0, 1, 0, 2, 0];
+        for (size, offset) in [(bitmap.len(), 38), (image.len(), 38 + bitmap.len())] {
+            data.extend([16, 16, 0, 0, 1, 0, 32, 0]);
+            data.extend((size as u32).to_le_bytes());
+            data.extend((offset as u32).to_le_bytes());
+        }
+        data.extend(bitmap);
+        data.extend(image);
+        data
+    }
+
+
+    #[test]
+    fn png_is_read_from_png_and_ico_files() {
+        let image = [PNG_SIGNATURE, b"image"].concat();
+        assert_eq!(png(&image), Some(image.as_slice()));
+        assert_eq!(png(&ico(&image)), Some(image.as_slice()));
+        assert_eq!(png(&ico(b"bitmap only")), None);
+        assert_eq!(png(b""), None);
+    }
+
+
+    #[test]
+    fn cache_is_named_after_the_host() {
+        let engine = Engine::new("local", "http://Localhost:8080/search?q={{term}}", "{{term}}", r"\s+", "+");
+        assert_eq!(origin(&engine).as_deref(), Some("http://localhost:8080"));
+        assert_eq!(path(Path::new("/cache"), &engine), Some(PathBuf::from("/cache/localhost_8080")));
+    }
+
+
+    #[test]
+    fn letter_circles_the_first_letter_or_digit() {
+        let engine = |name: &str| Engine::new(name, "https://example.test/?q={{term}}", "{{term}}", r"\s+", "+");
+        assert_eq!(letter(&engine("google")), 'Ⓖ');
+        assert_eq!(letter(&engine("-9gag")), '⑨');
+        assert_eq!(letter(&engine("日本")), '◌');
+        assert_eq!(icon(Path::new("/nonexistent"), &engine("bing"), Protocol::Kitty), "Ⓑ ");
+    }
+}
@@ -0,0 +1,101 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{IpAddr, TcpListener, TcpStream, UdpSocket};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use qrcode::QrCode;
+use qrcode::render::unicode::Dense1x2;
+use rand::distributions::{Alphanumeric, DistString};
+
+use crate::opensearch::escape;
+
+
+/// Seconds the server waits for the phone to open the search, unless told otherwise
+pub const DEFAULT_TIMEOUT: u64 = 120;
+
+
+/// Address of the machine on the local network: the one of the interface a packet to a public address would leave
+/// through. Connecting a UDP socket sends nothing, so no network access is needed.
+fn lan_address() -> Result<IpAddr, io::Error> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.connect("192.0.2.1:80")?;
+    let address = socket.local_addr()?.ip();
+
+    if address.is_loopback() || address.is_unspecified() {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not connected to a local network"))
+    } else {
+        Ok(address)
+    }
+}
+
+
+/// Renders the text as a QR code made of half blocks, two modules per character, readable from the terminal
+fn qr(text: &str) -> Result<String, io::Error> {
+    let code = QrCode::new(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    Ok(code.render::<Dense1x2>().quiet_zone(true).build())
+}
+
+
+/// Answers the request: the path holding the token is redirected to the URL, anything else is not found. Returns
+/// whether the request was redirected.
+fn answer(stream: TcpStream, token: &str, url: &str) -> Result<bool, io::Error> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut request = String::new();
+    BufReader::new(&stream).read_line(&mut request)?;
+
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+    let redirected = path.trim_start_matches('/') == token;
+    let response = if redirected {
+        let body = format!("<a href=\"{0}\">{0}</a>", escape(url));
+        format!("HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", url, body.len(), body)
+    } else {
+        String::from("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+    };
+
+    (&stream).write_all(response.as_bytes())?;
+    Ok(redirected)
+}
+
+
+/// Entry point of the `handoff` subcommand. Serves a redirect to the URL on the local network, under a random path,
+/// and prints a QR code pointing at it, so the search can be continued on a phone on the same network. The server
+/// stops once the redirect is followed, or after `timeout`.
+pub fn serve(url: &str, port: u16, timeout: Duration) -> Result<(), io::Error> {
+    if url.contains(['\r', '\n']) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "The URL contains line breaks"));
+    }
+
+    let address = lan_address()?;
+    let listener = TcpListener::bind((IpAddr::from([0, 0, 0, 0]), port))?;
+    listener.set_nonblocking(true)?;
+
+    let token = Alphanumeric.sample_string(&mut rand::thread_rng(), 12);
+    let link = format!("http://{}:{}/{}", address, listener.local_addr()?.port(), token);
+    info!("Serving the handoff of {} at {}", url, link);
+
+    println!("{}", qr(&link)?);
+    println!("Scan the code or open {} on the same network, within {} seconds", link, timeout.as_secs());
+
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                stream.set_nonblocking(false)?;
+                match answer(stream, &token, url) {
+                    Ok(true) => {
+                        info!("Search handed off to {}", peer);
+                        println!("Opened on {}", peer.ip());
+                        return Ok(());
+                    }
+                    Ok(false) => {}
+                    Err(e) => warn!("Unable to answer {}. Error: {}", peer, e),
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(Duration::from_millis(100)),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(io::Error::new(io::ErrorKind::TimedOut, "Nobody opened the search in time"))
+}
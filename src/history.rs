@@ -0,0 +1,107 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use chrono::{DateTime, Local};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use crate::{link, print_json, print_lines};
+
+
+/// Search made through the program, as recorded in the history file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Entry {
+    /// Moment in which the search was made
+    pub timestamp: DateTime<Local>,
+
+    /// Name of the engine used
+    pub engine: String,
+
+    /// Term searched, before being treated by the engine
+    pub term: String,
+
+    /// URL opened
+    pub url: String,
+
+    /// Session active when the search was made, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+}
+
+
+/// Appends an entry to the history file
+pub fn record(path: &Path, entry: &Entry) {
+    let result = OpenOptions::new().create(true).append(true).open(path).and_then(|mut file| {
+        let line = serde_json::to_string(entry).map_err(io::Error::other)?;
+        writeln!(file, "{}", line)
+    });
+
+    match result {
+        Ok(_) => info!("Search recorded in the history"),
+        Err(e) => error!("Unable to write to the history file. Error: {}", e),
+    }
+}
+
+
+/// Reads every entry of the history file, from the oldest to the latest
+pub fn entries(path: &Path) -> Vec<Entry> {
+    match File::open(path) {
+        Ok(file) => BufReader::new(file).lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+
+/// Replaces the content of the history file with the entries passed as an argument
+pub fn rewrite(path: &Path, entries: &[Entry]) -> Result<(), io::Error> {
+    let mut file = File::create(path)?;
+    for entry in entries {
+        writeln!(file, "{}", serde_json::to_string(entry).map_err(io::Error::other)?)?;
+    }
+    Ok(())
+}
+
+
+/// Part of the history to print, counted from the latest search, see [list]
+pub struct Page {
+    /// Number of searches printed, all of them if null
+    pub limit: Option<usize>,
+
+    /// Number of the latest searches skipped, to page back in time
+    pub offset: usize,
+
+    /// Whether the searches are shown through the pager
+    pub pager: bool,
+}
+
+
+/// Prints the searches of the page, from the oldest to the latest, optionally only the ones of a session. The
+/// URLs are written as OSC 8 hyperlinks if asked to, and the entries as a JSON list with `json`.
+pub fn list(path: &Path, session: Option<&str>, page: Page, hyperlink: bool, json: bool) {
+    let entries: Vec<Entry> = entries(path).into_iter()
+        .filter(|entry| session.is_none() || entry.session.as_deref() == session)
+        .collect();
+    let end = entries.len().saturating_sub(page.offset);
+    let start = page.limit.map_or(0, |limit| end.saturating_sub(limit));
+    let entries = &entries[start..end];
+
+    if json {
+        print_json(entries);
+        return;
+    }
+    if entries.is_empty() {
+        println!("No searches recorded");
+    }
+
+    let lines: Vec<String> = entries.iter()
+        .map(|entry| {
+            let url = link(&entry.url, &entry.url, hyperlink);
+            format!("{}  {:<15} {}  {}", entry.timestamp.format("%Y-%m-%d %H:%M:%S"), entry.engine, entry.term, url)
+        })
+        .collect();
+    print_lines(&lines, page.pager);
+}
@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use chrono::Local;
+use home::home_dir;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::Engine;
+
+
+/// User-Agent of the requests, unless the settings say otherwise, identifying the program since some engines block
+/// the default ones of HTTP libraries
+pub const DEFAULT_USER_AGENT: &str = concat!("search/", env!("CARGO_PKG_VERSION"), " (+https://github.com/ArthurValada/terminal-search)");
+
+
+/// SOCKS proxy of a Tor daemon running with its default configuration
+pub const DEFAULT_TOR_PROXY: &str = "socks5://127.0.0.1:9050";
+
+
+/// Settings of every request the program makes over the network, kept under `settings.http`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct HttpSettings {
+    /// User-Agent header of the requests
+    pub user_agent: String,
+
+    /// Seconds during which the responses of the suggestions are cached, 0 disabling the cache
+    pub cache_ttl_secs: u64,
+
+    /// Milliseconds after which a request is given up
+    pub timeout_ms: u64,
+
+    /// Proxy the requests go through, such as `http://proxy.corp:3128` or `socks5://localhost:1080`. If null, the
+    /// `ALL_PROXY`, `HTTPS_PROXY` and `HTTP_PROXY` environment variables are honored.
+    pub proxy: Option<String>,
+
+    /// SOCKS proxy of the local Tor daemon, which the requests go through instead with `--tor` or for the engines
+    /// with `via_tor`
+    pub tor_proxy: String,
+
+    /// Whether the requests go through [Self::tor_proxy], as set by [Self::for_engine]. They are then never made
+    /// without it, even if it's invalid.
+    #[serde(skip)]
+    pub tor: bool,
+}
+
+
+impl Default for HttpSettings {
+    fn default() -> HttpSettings {
+        HttpSettings {
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            cache_ttl_secs: 300,
+            timeout_ms: 3000,
+            proxy: None,
+            tor_proxy: DEFAULT_TOR_PROXY.to_string(),
+            tor: false,
+        }
+    }
+}
+
+
+impl HttpSettings {
+    /// Settings of the requests made to the engine, going through [Self::tor_proxy] when `tor` is given, as with
+    /// `--tor`, or the engine has `via_tor`
+    pub fn for_engine(&self, engine: &Engine, tor: bool) -> HttpSettings {
+        let mut settings = self.clone();
+        if tor || engine.via_tor {
+            settings.proxy = Some(self.tor_proxy.clone());
+            settings.tor = true;
+        }
+        settings
+    }
+}
+
+
+/// Starts building the agent making the requests over the network, according to the settings. An invalid proxy is
+/// ignored, with a warning, unless the requests must go through Tor, in which case it fails rather than letting them
+/// out without it.
+pub fn agent(settings: &HttpSettings) -> Result<ureq::AgentBuilder, io::Error> {
+    let builder = ureq::AgentBuilder::new()
+        .user_agent(&settings.user_agent)
+        .timeout(Duration::from_millis(settings.timeout_ms))
+        .try_proxy_from_env(true);
+
+    match settings.proxy.as_deref().map(ureq::Proxy::new) {
+        Some(Ok(proxy)) => Ok(builder.proxy(proxy)),
+        Some(Err(e)) if settings.tor => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid Tor proxy {} (settings.http.tor_proxy), the request was not made: {}", settings.tor_proxy, e),
+        )),
+        Some(Err(e)) => {
+            warn!("Ignoring the invalid proxy of the settings. Error: {}", e);
+            Ok(builder)
+        }
+        None => Ok(builder),
+    }
+}
+
+
+/// Credentials sent with HTTP basic authentication
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BasicAuth {
+    pub username: String,
+
+    /// Password, better left out of the configuration file in favor of [Self::password_env]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+
+    /// Environment variable holding the password
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_env: Option<String>,
+}
+
+
+impl BasicAuth {
+    /// Value of the `Authorization` header
+    fn header(&self) -> String {
+        let password = self.password_env.as_deref()
+            .and_then(|variable| std::env::var(variable).inspect_err(|_| warn!("The variable {} is not set", variable)).ok())
+            .or_else(|| self.password.clone())
+            .unwrap_or_default();
+        format!("Basic {}", STANDARD.encode(format!("{}:{}", self.username, password)))
+    }
+}
+
+
+/// Options of the requests made to the engine by the features that reach it over the network, such as the online
+/// checks of `doctor`, so that internal tools behind simple authentication work with them as well
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct HttpOptions {
+    /// Headers added to every request, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+
+    /// Cookies file in the Netscape format, as exported by browsers and curl, whose cookies for the host of the
+    /// request are sent with it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cookies_file: Option<PathBuf>,
+
+    /// Credentials sent with HTTP basic authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub basic_auth: Option<BasicAuth>,
+}
+
+
+/// Expands a leading `~` of the path into the home directory
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), home_dir()) {
+        (Ok(relative), Some(home)) => home.join(relative),
+        _ => path.to_path_buf(),
+    }
+}
+
+
+/// Reads the cookies of the Netscape cookies file that apply to the host and path, as a `Cookie` header value.
+/// Expired cookies are left out.
+fn cookies(file: &Path, host: &str, path: &str) -> Option<String> {
+    let content = fs::read_to_string(expand_home(file))
+        .inspect_err(|e| warn!("Unable to read the cookies file {:?}. Error: {}", file, e))
+        .ok()?;
+    let now = Local::now().timestamp();
+
+    let cookies: Vec<String> = content.lines()
+        .map(|line| line.strip_prefix("#HttpOnly_").unwrap_or(line))
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [domain, _, cookie_path, _, expires, name, value] = fields[..] else {
+                return None;
+            };
+            let domain = domain.trim_start_matches('.');
+            let expired = expires.parse::<i64>().is_ok_and(|expires| expires != 0 && expires < now);
+            let matches = (host == domain || host.ends_with(&format!(".{}", domain))) && path.starts_with(cookie_path);
+            (matches && !expired).then(|| format!("{}={}", name, value))
+        })
+        .collect();
+
+    (!cookies.is_empty()).then(|| cookies.join("; "))
+}
+
+
+/// Prepares a GET request of the URL to the engine, with the HTTP options it declares
+pub fn get(agent: &ureq::Agent, engine: &Engine, url: &str) -> ureq::Request {
+    let mut request = agent.get(url);
+    let Some(options) = &engine.http else {
+        return request;
+    };
+
+    for (name, value) in &options.headers {
+        request = request.set(name, value);
+    }
+    if let Some(auth) = &options.basic_auth {
+        request = request.set("Authorization", &auth.header());
+    }
+    if let Some(file) = &options.cookies_file {
+        let cookies = request.request_url().ok().and_then(|url| cookies(file, url.host(), url.path()));
+        if let Some(cookies) = cookies {
+            request = request.set("Cookie", &cookies);
+        }
+    }
+    request
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn invalid_tor_proxies_fail_instead_of_going_direct() {
+        let engine = Engine::new("example", "https://example.test/?q={{term}}", "{{term}}", r"\s+", "+");
+        let settings = HttpSettings { proxy: Some("bogus://proxy".to_string()), tor_proxy: "bogus://tor".to_string(), ..HttpSettings::default() };
+
+        assert!(agent(&settings).is_ok());
+        assert_eq!(agent(&settings.for_engine(&engine, true)).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert!(agent(&HttpSettings::default().for_engine(&engine, true)).is_ok());
+    }
+}
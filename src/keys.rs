@@ -0,0 +1,205 @@
+use std::io;
+
+use serde_yaml::{Mapping, Value};
+
+use crate::Configuration;
+
+
+/// Error returned when the key does not name any setting of the configuration
+fn unknown_key(key: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("Unknown key '{}'", key))
+}
+
+
+/// Returns the child of the node named by the part of a dotted key: a key of a mapping or an index of a sequence
+fn child<'a>(node: &'a Value, part: &str) -> Option<&'a Value> {
+    match node {
+        Value::Mapping(mapping) => mapping.get(part),
+        Value::Sequence(sequence) => sequence.get(part.parse::<usize>().ok()?),
+        _ => None,
+    }
+}
+
+
+/// Places the value at the dotted key, creating the mappings missing along the way
+fn insert(root: &mut Value, key: &str, value: Value) -> Result<(), io::Error> {
+    let mut node = root;
+    let mut parts = key.split('.').peekable();
+
+    while let Some(part) = parts.next() {
+        if part.is_empty() {
+            return Err(unknown_key(key));
+        }
+        if node.is_null() {
+            *node = Value::Mapping(Mapping::new());
+        }
+
+        let last = parts.peek().is_none();
+        node = match node {
+            Value::Mapping(mapping) => {
+                let entry = mapping.entry(Value::String(part.to_string())).or_insert(Value::Null);
+                if last {
+                    *entry = value;
+                    return Ok(());
+                }
+                entry
+            }
+            Value::Sequence(sequence) => {
+                let element = part.parse::<usize>().ok()
+                    .and_then(|index| sequence.get_mut(index))
+                    .ok_or_else(|| unknown_key(key))?;
+                if last {
+                    *element = value;
+                    return Ok(());
+                }
+                element
+            }
+            _ => return Err(unknown_key(key)),
+        };
+    }
+
+    Err(unknown_key(key))
+}
+
+
+/// Converts the value back into a configuration, failing if any of its keys is not a setting of the configuration,
+/// since those would be silently dropped
+fn parse(root: Value, key: &str) -> Result<Configuration, io::Error> {
+    let mut ignored = false;
+    let config: Configuration = serde_ignored::deserialize(root, |_| ignored = true)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid value for '{}': {}", key, e)))?;
+
+    if ignored {
+        Err(unknown_key(key))
+    } else {
+        Ok(config)
+    }
+}
+
+
+/// Returns the value of the setting named by the dotted key, such as `devtools_endpoint` or `groups.research`.
+/// Top-level settings left at their default are not written to the file, so their value is found by trying the
+/// defaults omitted when writing it (false, an empty list, an empty mapping and null) until one is accepted for the
+/// key. Missing nested keys, such as an undeclared group, are null.
+pub fn get(config: &Configuration, key: &str) -> Result<Value, io::Error> {
+    let root = serde_yaml::to_value(config).map_err(io::Error::other)?;
+
+    if let Some(value) = key.split('.').try_fold(&root, |node, part| child(node, part)) {
+        return Ok(value.clone());
+    }
+
+    let defaults = if key.contains('.') {
+        vec![Value::Null]
+    } else {
+        vec![Value::Bool(false), Value::Sequence(vec![]), Value::Mapping(Mapping::new()), Value::Null]
+    };
+    for default in defaults {
+        let mut probe = root.clone();
+        insert(&mut probe, key, default.clone())?;
+        match parse(probe, key) {
+            Ok(_) => return Ok(default),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Err(e),
+            Err(_) => continue,
+        }
+    }
+
+    Err(unknown_key(key))
+}
+
+
+/// Sets the setting named by the dotted key. The value is read as YAML, so `true`, `42` and `[a, b]` are a boolean,
+/// a number and a list, and `null` unsets the setting. The configuration is only changed if the result is valid.
+pub fn set(config: &mut Configuration, key: &str, value: &str) -> Result<(), io::Error> {
+    let value = serde_yaml::from_str::<Value>(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    set_value(config, key, value)
+}
+
+
+/// Sets the setting named by the dotted key to the value, already parsed, see [set]. The default engine is set as
+/// [Configuration::set_default] does, failing if no engine has the name.
+pub fn set_value(config: &mut Configuration, key: &str, value: Value) -> Result<(), io::Error> {
+    if let ("default_engine", Value::String(name)) = (key, &value) {
+        return config.set_default(name.clone());
+    }
+
+    let mut root = serde_yaml::to_value(&*config).map_err(io::Error::other)?;
+    insert(&mut root, key, value)?;
+
+    let mut updated = parse(root, key)?;
+    updated.file_path = config.file_path.clone();
+    updated.tampered = config.tampered;
+    updated.modified = true;
+    *config = updated;
+    Ok(())
+}
+
+
+/// Prints the value, scalars as they are and the rest as YAML
+pub fn print(value: &Value) {
+    match value {
+        Value::String(text) => println!("{}", text),
+        other => print!("{}", serde_yaml::to_string(other).unwrap_or_default()),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::Engine;
+
+
+    fn config() -> Configuration {
+        let engine = Engine::new("duckduckgo", "https://duckduckgo.com/?q={{term}}", "{{term}}", r"\s+", "+");
+        Configuration::new(PathBuf::from("/nonexistent/search_config.yaml"), None, vec![engine])
+    }
+
+
+    #[test]
+    fn default_engine_must_name_an_engine() {
+        let mut config = config();
+        assert_eq!(set(&mut config, "default_engine", "nosuch").unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert!(config.default_engine.is_none());
+
+        set(&mut config, "default_engine", "duck").unwrap();
+        assert_eq!(config.default_engine.as_deref(), Some("duckduckgo"));
+
+        set(&mut config, "default_engine", "null").unwrap();
+        assert!(config.default_engine.is_none());
+    }
+
+
+    #[test]
+    fn dotted_keys_reach_nested_settings() {
+        let mut config = config();
+        set(&mut config, "settings.http.timeout_ms", "500").unwrap();
+        assert_eq!(config.settings.http.timeout_ms, 500);
+        assert_eq!(get(&config, "settings.http.timeout_ms").unwrap(), Value::from(500));
+
+        set(&mut config, "groups.research", "[duckduckgo]").unwrap();
+        assert_eq!(get(&config, "groups.research.0").unwrap(), Value::from("duckduckgo"));
+        assert_eq!(get(&config, "groups.missing").unwrap(), Value::Null);
+    }
+
+
+    #[test]
+    fn omitted_top_level_settings_read_as_their_default() {
+        let config = config();
+        assert_eq!(get(&config, "rotation").unwrap(), Value::Sequence(vec![]));
+        assert_eq!(get(&config, "default_engine").unwrap(), Value::Null);
+    }
+
+
+    #[test]
+    fn unknown_keys_and_invalid_values_leave_the_configuration_alone() {
+        let mut config = config();
+        assert_eq!(set(&mut config, "settings.nosuch", "1").unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert_eq!(set(&mut config, "settings..history", "1").unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert_eq!(get(&config, "nosuch").unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert_eq!(set(&mut config, "settings.history", "often").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert!(config.settings.history);
+        assert!(!config.modified);
+    }
+}
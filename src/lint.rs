@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use log::{info, warn};
+use regex::Regex;
+
+use crate::{macros, normalize_name, Configuration, EngineKind};
+
+/// Terms the regex of each engine is tried against, to find regexes that match nothing
+const SAMPLE_TERMS: [&str; 5] = ["hello world", "rust borrow checker", "C++ templates", "São Paulo", "what-is_this.rs?"];
+
+/// Problems that can be found in the configuration
+#[derive(Debug, Clone, PartialEq)]
+pub enum Finding {
+    /// The pattern of the engine never occurs in its url pattern, so the term is never placed in the URL
+    PatternMissing { engine: String },
+
+    /// The regex of the engine does not compile
+    InvalidRegex { engine: String, error: String },
+
+    /// The regex of the engine matches none of the [SAMPLE_TERMS]
+    RegexMatchesNothing { engine: String },
+
+    /// The regex of the engine matches the empty string, so the replacement is inserted at every position
+    RegexMatchesEverything { engine: String },
+
+    /// A `{date:FORMAT}` placeholder of the url pattern has an invalid format
+    InvalidDateFormat { engine: String, format: String },
+
+    /// The charset of the encoding of the engine is unknown, so the engine is unable to generate any URL
+    UnknownCharset { engine: String, charset: String },
+
+    /// The default engine is not configured anymore
+    DanglingDefault { name: String },
+
+    /// The alias is already the name or an alias of another engine, or the name of the engine itself
+    DuplicateAlias { engine: String, alias: String, owner: String },
+
+    /// A member of the group refers to no engine, or to several, so searching the group fails
+    UnresolvedMember { group: String, member: String },
+
+    /// The group has no members, so searching it does nothing
+    EmptyGroup { group: String },
+
+    /// An engine of the rotation refers to no engine, or to several, so `@rotate` fails when it comes to it
+    UnresolvedRotation { member: String },
+}
+
+
+impl Finding {
+    /// Whether the finding can be fixed mechanically by `lint --fix`
+    pub fn fixable(&self) -> bool {
+        matches!(self, Finding::DanglingDefault { .. } | Finding::DuplicateAlias { .. } | Finding::UnresolvedMember { .. }
+            | Finding::EmptyGroup { .. } | Finding::UnresolvedRotation { .. })
+    }
+
+
+    /// Applies the mechanical fix of the finding to the configuration
+    pub fn fix(&self, config: &mut Configuration) {
+        match self {
+            Finding::DanglingDefault { name } => {
+                info!("Clearing the dangling default engine {}", name);
+                config.default_engine = None;
+                config.modified = true;
+            }
+            Finding::DuplicateAlias { engine, alias, .. } => {
+                info!("Removing the duplicate alias {} from {}", alias, engine);
+                if config.engines.modify(engine, |element| element.aliases.retain(|value| value != alias)).is_some() {
+                    config.modified = true;
+                }
+            }
+            Finding::UnresolvedMember { group, member } => {
+                info!("Removing {} from the group {}", member, group);
+                if let Some(members) = config.groups.get_mut(group) {
+                    members.retain(|value| value != member);
+                    config.modified = true;
+                }
+            }
+            Finding::EmptyGroup { group } => {
+                info!("Removing the empty group {}", group);
+                if config.groups.remove(group).is_some() {
+                    config.modified = true;
+                }
+            }
+            Finding::UnresolvedRotation { member } => {
+                info!("Removing {} from the rotation", member);
+                config.rotation.retain(|value| value != member);
+                config.modified = true;
+            }
+            _ => warn!("Attempting to fix a finding that can't be fixed mechanically"),
+        }
+    }
+}
+
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Finding::PatternMissing { engine } => write!(f, "{}: the pattern never occurs in the url pattern", engine),
+            Finding::InvalidRegex { engine, error } => write!(f, "{}: invalid regex. {}", engine, error),
+            Finding::RegexMatchesNothing { engine } => write!(f, "{}: the regex matches none of the sample terms", engine),
+            Finding::RegexMatchesEverything { engine } => write!(f, "{}: the regex matches the empty string, so it matches everything", engine),
+            Finding::InvalidDateFormat { engine, format } => write!(f, "{}: invalid date format {}", engine, format),
+            Finding::UnknownCharset { engine, charset } => write!(f, "{}: unknown charset {}", engine, charset),
+            Finding::DanglingDefault { name } => write!(f, "the default engine {} does not exist anymore", name),
+            Finding::DuplicateAlias { engine, alias, owner } if engine == owner => write!(f, "{}: the alias {} repeats the engine name", engine, alias),
+            Finding::DuplicateAlias { engine, alias, owner } => write!(f, "{}: the alias {} already refers to {}", engine, alias, owner),
+            Finding::UnresolvedMember { group, member } => write!(f, "group {}: {} does not refer to a single engine", group, member),
+            Finding::EmptyGroup { group } => write!(f, "group {}: no engines, so it is never searched", group),
+            Finding::UnresolvedRotation { member } => write!(f, "rotation: {} does not refer to a single engine", member),
+        }
+    }
+}
+
+
+/// Inspects the configuration, returning every finding
+pub fn check(config: &Configuration) -> Vec<Finding> {
+    let mut findings = vec![];
+    let engines: Vec<_> = config.engines.iter().collect();
+
+    for engine in &engines {
+        if engine.kind == EngineKind::Web && !engine.url_pattern.contains(&engine.pattern) {
+            findings.push(Finding::PatternMissing { engine: engine.name.clone() });
+        }
+
+        match Regex::new(&engine.regex) {
+            Ok(regex) => {
+                if regex.is_match("") {
+                    findings.push(Finding::RegexMatchesEverything { engine: engine.name.clone() });
+                } else if !SAMPLE_TERMS.iter().any(|term| regex.is_match(term)) {
+                    findings.push(Finding::RegexMatchesNothing { engine: engine.name.clone() });
+                }
+            }
+            Err(e) => findings.push(Finding::InvalidRegex { engine: engine.name.clone(), error: e.to_string() }),
+        }
+
+        for format in macros::invalid_date_formats(&engine.url_pattern) {
+            findings.push(Finding::InvalidDateFormat { engine: engine.name.clone(), format });
+        }
+
+        if let Some(encoding) = engine.encoding.as_ref().filter(|encoding| encoding.charset().is_err()) {
+            findings.push(Finding::UnknownCharset { engine: engine.name.clone(), charset: encoding.charset.clone() });
+        }
+    }
+
+    if let Some(name) = &config.default_engine {
+        if !config.contains(name) {
+            findings.push(Finding::DanglingDefault { name: name.clone() });
+        }
+    }
+
+    let mut owners: HashMap<String, String> = engines.iter()
+        .map(|engine| (normalize_name(&engine.name), engine.name.clone()))
+        .collect();
+
+    for engine in &engines {
+        for alias in &engine.aliases {
+            match owners.get(&normalize_name(alias)) {
+                Some(owner) => findings.push(Finding::DuplicateAlias {
+                    engine: engine.name.clone(),
+                    alias: alias.clone(),
+                    owner: owner.clone(),
+                }),
+                None => {
+                    owners.insert(normalize_name(alias), engine.name.clone());
+                }
+            }
+        }
+    }
+
+    for (group, members) in &config.groups {
+        if members.is_empty() {
+            findings.push(Finding::EmptyGroup { group: group.clone() });
+        }
+        for member in members.iter().filter(|member| config.where_name(member.to_string()).is_err()) {
+            findings.push(Finding::UnresolvedMember { group: group.clone(), member: member.clone() });
+        }
+    }
+
+    for member in config.rotation.iter().filter(|member| config.where_name(member.to_string()).is_err()) {
+        findings.push(Finding::UnresolvedRotation { member: member.clone() });
+    }
+
+    findings
+}
+
+
+/// Entry point of the `lint` subcommand. Prints every finding and, if `fix` is set, fixes the mechanical ones.
+pub fn run(config: &mut Configuration, fix: bool) {
+    let findings = check(config);
+
+    if findings.is_empty() {
+        println!("No problems found");
+        return;
+    }
+
+    for finding in &findings {
+        if fix && finding.fixable() {
+            finding.fix(config);
+            println!("✔ fixed: {}", finding);
+        } else if finding.fixable() {
+            println!("✘ {} (fixable with --fix)", finding);
+        } else {
+            println!("✘ {}", finding);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::Engine;
+
+
+    fn config() -> Configuration {
+        let engines = ["duckduckgo", "google"].iter()
+            .map(|name| Engine::new(name, &format!("https://{}.test/?q={{{{term}}}}", name), "{{term}}", r"\s+", "+"))
+            .collect();
+        Configuration::new(PathBuf::from("/nonexistent/search_config.yaml"), Some("duckduckgo".to_string()), engines)
+    }
+
+
+    #[test]
+    fn clean_configuration_has_no_findings() {
+        let mut config = config();
+        config.groups.insert("web".to_string(), vec!["duck".to_string(), "google".to_string()]);
+        config.rotation = vec!["google".to_string()];
+        assert_eq!(check(&config), vec![]);
+    }
+
+
+    #[test]
+    fn groups_and_rotation_are_checked_and_fixed() {
+        let mut config = config();
+        config.groups.insert("web".to_string(), vec!["google".to_string(), "bing".to_string()]);
+        config.groups.insert("unused".to_string(), vec![]);
+        config.rotation = vec!["yahoo".to_string(), "google".to_string()];
+
+        let findings = check(&config);
+        assert_eq!(findings, vec![
+            Finding::EmptyGroup { group: "unused".to_string() },
+            Finding::UnresolvedMember { group: "web".to_string(), member: "bing".to_string() },
+            Finding::UnresolvedRotation { member: "yahoo".to_string() },
+        ]);
+
+        for finding in findings.iter().filter(|finding| finding.fixable()) {
+            finding.fix(&mut config);
+        }
+        assert_eq!(config.groups.get("web"), Some(&vec!["google".to_string()]));
+        assert!(!config.groups.contains_key("unused"));
+        assert_eq!(config.rotation, ["google"]);
+        assert_eq!(check(&config), vec![]);
+    }
+
+
+    #[test]
+    fn engine_problems_are_found() {
+        let mut config = config();
+        config.push(Engine::new("broken", "https://broken.test/", "{{term}}", "(", "+")).unwrap();
+        config.push(Engine::new("everything", "https://all.test/?q={{term}}", "{{term}}", "x*", "+")).unwrap();
+        config.default_engine = Some("gone".to_string());
+
+        let findings = check(&config);
+        assert!(findings.contains(&Finding::PatternMissing { engine: "broken".to_string() }));
+        assert!(findings.iter().any(|finding| matches!(finding, Finding::InvalidRegex { engine, .. } if engine == "broken")));
+        assert!(findings.contains(&Finding::RegexMatchesEverything { engine: "everything".to_string() }));
+        assert!(findings.contains(&Finding::DanglingDefault { name: "gone".to_string() }));
+    }
+}
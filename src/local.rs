@@ -0,0 +1,181 @@
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use edit::get_editor;
+use inquire::Select;
+use log::{error, info, warn};
+
+/// Prefix of the terms searched in the local manual pages, whatever the engine
+pub const MAN_PREFIX: &str = "man:";
+
+
+/// Page asked for by a term prefixed with [MAN_PREFIX], as `man:tar`, whatever the engine
+pub fn man_page(term: &str) -> Option<&str> {
+    term.trim_start().strip_prefix(MAN_PREFIX)
+}
+
+
+/// Splits the term into the names given to man, refusing the ones starting with `-`, which man would take as
+/// options, some of them, such as `-P`, running commands
+fn man_words(term: &str) -> Result<Vec<&str>, io::Error> {
+    let words: Vec<&str> = term.split_whitespace().collect();
+    if words.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Attempting to search an empty term in the manual pages"));
+    }
+    match words.iter().find(|word| word.starts_with('-')) {
+        Some(word) => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Refusing to pass {} to man, it looks like an option", word))),
+        None => Ok(words),
+    }
+}
+
+
+/// Opens the manual page of the term in the terminal, if there is one, otherwise lists the pages related to it
+/// as `man -k` does
+pub fn man(term: &str) {
+    let words = match man_words(term) {
+        Ok(words) => words,
+        Err(e) => {
+            error!("{}", e);
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    let exists = Command::new("man")
+        .arg("-w")
+        .arg("--")
+        .args(&words)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+
+    let status = if exists {
+        info!("Opening the manual page of {}", term);
+        Command::new("man").arg("--").args(&words).status()
+    } else {
+        info!("No manual page named {}, searching the descriptions", term);
+        Command::new("man").arg("-k").arg("--").args(&words).status()
+    };
+
+    if let Err(e) = status {
+        error!("Unable to run man. Error: {}", e);
+        eprintln!("Unable to run man: {}", e);
+    }
+}
+
+
+/// A line matched by ripgrep
+struct Match {
+    /// File containing the line
+    path: PathBuf,
+
+    /// Number of the line, starting at one
+    line: u64,
+
+    /// Content of the line
+    text: String,
+}
+
+
+impl fmt::Display for Match {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.path.display(), self.line, self.text.trim())
+    }
+}
+
+
+/// Runs ripgrep for the term under the root directory, returning the matched lines
+fn ripgrep(term: &str, root: &Path) -> Result<Vec<Match>, io::Error> {
+    let output = Command::new("rg")
+        .args(["--line-number", "--no-heading", "--null", "--color", "never", "--smart-case", "--fixed-strings", "--"])
+        .arg(term)
+        .arg(root)
+        .output()?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().filter_map(|line| {
+        let (path, rest) = line.split_once('\0')?;
+        let (number, text) = rest.split_once(':')?;
+        Some(Match { path: PathBuf::from(path), line: number.parse().ok()?, text: text.to_string() })
+    }).collect())
+}
+
+
+/// Opens the file in the configured editor, at the line passed as an argument
+fn open_in_editor(path: &Path, line: u64) {
+    match get_editor() {
+        Ok(editor) => {
+            if let Err(e) = Command::new(editor).arg(format!("+{}", line)).arg(path).status() {
+                error!("Unable to open the editor. Error: {}", e);
+            }
+        }
+        Err(e) => error!("Unable to find an editor. Error: {}", e),
+    }
+}
+
+
+/// Searches the term in the files under the root directory with ripgrep, presenting the matches in the terminal.
+/// The selected match is opened in the editor; when no selection can be made, the matches are just printed.
+pub fn grep(term: &str, root: Option<&Path>) {
+    let Some(root) = root else {
+        error!("Local grep engine without a root directory");
+        eprintln!("The engine has no root directory to search in");
+        return;
+    };
+
+    let matches = match ripgrep(term.trim(), root) {
+        Ok(matches) => matches,
+        Err(e) => {
+            error!("Unable to run ripgrep. Error: {}", e);
+            eprintln!("Unable to run ripgrep: {}", e);
+            return;
+        }
+    };
+
+    if matches.is_empty() {
+        println!("No matches for {} in {}", term.trim(), root.display());
+        return;
+    }
+
+    if !io::stdin().is_terminal() {
+        for found in matches {
+            println!("{}", found);
+        }
+        return;
+    }
+
+    match Select::new("Open which match?", matches).prompt() {
+        Ok(selected) => open_in_editor(&selected.path, selected.line),
+        Err(e) => warn!("No match selected. {}", e),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn man_page_needs_the_prefix_at_the_start() {
+        assert_eq!(man_page("  man:tar"), Some("tar"));
+        assert_eq!(man_page("\"man:tar\""), None);
+        assert_eq!(man_page("site:wiki man:tar"), None);
+    }
+
+
+    #[test]
+    fn man_words_splits_the_term() {
+        assert_eq!(man_words("  git   commit ").unwrap(), vec!["git", "commit"]);
+    }
+
+
+    #[test]
+    fn man_words_refuses_options_and_empty_terms() {
+        assert!(man_words("-P'touch /tmp/x' ls").is_err());
+        assert!(man_words("ls --help").is_err());
+        assert!(man_words("   ").is_err());
+    }
+}
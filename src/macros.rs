@@ -0,0 +1,107 @@
+use std::sync::LazyLock;
+
+use chrono::{Duration, Local, Months, NaiveDate};
+use chrono::format::{Item, StrftimeItems};
+use log::warn;
+use regex::{Captures, Regex};
+use selection::get_text;
+
+use crate::clipboard;
+
+
+/// Format of the dates the `%date%` macros expand to
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+
+/// Date placeholders of the url patterns, `{date:FORMAT}`, FORMAT being a strftime-like format
+static DATE_PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{date:([^}]*)\}").unwrap());
+
+
+/// Macros of the terms and url patterns, see [expand]
+static MACRO: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"%(clip|sel|date)(?:([+-])(\d+)([dwmy]))?%").unwrap());
+
+
+/// Whether the strftime-like format is valid, since formatting a date with an invalid one panics
+fn valid_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+
+/// Formats of the date placeholders of the url pattern that are not valid strftime-like formats
+pub fn invalid_date_formats(pattern: &str) -> Vec<String> {
+    DATE_PLACEHOLDER.captures_iter(pattern)
+        .map(|captures| captures[1].to_string())
+        .filter(|format| !valid_format(format))
+        .collect()
+}
+
+
+/// Fills the `{date:FORMAT}` placeholders of the url pattern with the current date and time formatted as FORMAT
+/// says, as in `https://arxiv.org/list/cs/new?date={date:%Y-%m-%d}`. Placeholders with an invalid format are left
+/// untouched, `lint` reports them.
+pub fn fill_dates(pattern: &str) -> String {
+    let now = Local::now();
+    DATE_PLACEHOLDER.replace_all(pattern, |captures: &Captures| {
+        if valid_format(&captures[1]) {
+            now.format(&captures[1]).to_string()
+        } else {
+            warn!("Invalid date format in {}", &captures[0]);
+            captures[0].to_string()
+        }
+    }).into_owned()
+}
+
+
+/// Today shifted by the offset of a `%date%` macro, such as `-7d`, `+2w`, `-1m` or `-1y`
+fn shifted_date(today: NaiveDate, sign: &str, amount: u32, unit: &str) -> Option<NaiveDate> {
+    let forward = sign == "+";
+    match unit {
+        "d" | "w" => {
+            let days = Duration::days(amount as i64 * if unit == "w" { 7 } else { 1 });
+            if forward { today.checked_add_signed(days) } else { today.checked_sub_signed(days) }
+        }
+        _ => {
+            let months = Months::new(amount * if unit == "y" { 12 } else { 1 });
+            if forward { today.checked_add_months(months) } else { today.checked_sub_months(months) }
+        }
+    }
+}
+
+
+/// Expands the macros in the text, before it's processed by the engine:
+/// - `%clip%`, the contents of the clipboard;
+/// - `%sel%`, the selected text (the primary selection on X11 and Wayland);
+/// - `%date%`, today's date, and `%date-7d%`-like macros, today shifted by a number of days (`d`), weeks (`w`),
+///   months (`m`) or years (`y`), formatted as `YYYY-MM-DD`.
+///
+/// Anything else between percent signs is left untouched, so URL encoded text is safe. Macros that can't be
+/// expanded, as `%clip%` without a clipboard utility, are removed with a warning.
+pub fn expand(text: &str) -> String {
+    if !text.contains('%') {
+        return text.to_string();
+    }
+
+    let today = Local::now().date_naive();
+    MACRO.replace_all(text, |captures: &Captures| {
+        match (&captures[1], captures.get(2)) {
+            ("clip", None) => clipboard::paste().map(|text| text.trim().to_string()).unwrap_or_else(|e| {
+                warn!("Unable to read the clipboard for %clip%. Error: {}", e);
+                String::new()
+            }),
+            ("sel", None) => get_text().trim().to_string(),
+            ("date", None) => today.format(DATE_FORMAT).to_string(),
+            ("date", Some(sign)) => {
+                let date = captures[3].parse().ok()
+                    .and_then(|amount| shifted_date(today, sign.as_str(), amount, &captures[4]));
+                match date {
+                    Some(date) => date.format(DATE_FORMAT).to_string(),
+                    None => {
+                        warn!("Date out of range in {}", &captures[0]);
+                        String::new()
+                    }
+                }
+            }
+            _ => captures[0].to_string(),
+        }
+    }).into_owned()
+}
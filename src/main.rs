@@ -1,18 +1,68 @@
-use std::{fs, io};
+mod apply;
+mod audit;
+mod backups;
+mod cache;
+mod clipboard;
+mod devtools;
+mod doctor;
+mod emulator;
+mod encoding;
+mod engines;
+mod favicons;
+mod handoff;
+mod history;
+mod http;
+mod keys;
+mod lint;
+mod local;
+mod macros;
+mod meta;
+mod opensearch;
+mod preprocess;
+mod progress;
+mod presets;
+mod reload;
+mod research;
+mod session;
+mod settings;
+mod state;
+mod suggest;
+mod surfraw;
+mod tutorial;
+mod watch;
+
+use std::{fmt, fs, io};
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{create_dir, File};
-use std::io::Write;
+use std::io::{BufWriter, IsTerminal, Write};
 use std::option::Option;
-use std::path::PathBuf;
-use std::str::FromStr;
-
-use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::Duration;
+
+use chrono::Local;
+use clap::{ArgGroup, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap::builder::PossibleValuesParser;
+use clap_complete::{generate, Shell};
 use edit::edit_file;
 use home::home_dir;
-use inquire::Text;
+use indicatif::ProgressBar;
+use inquire::{Confirm, Select, Text};
 use log::{error, info, LevelFilter, warn};
+use rayon::prelude::*;
 use regex::Regex;
 use selection::get_text;
+use cache::Cache;
+use emulator::Emulator;
+use engines::Engines;
+use settings::{LongUrls, MultiTerm, Opener, Settings};
+use state::State;
+use similar::TextDiff;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use strsim::levenshtein;
 use uuid::Uuid;
 
 /// Function responsible for redirecting [info!], [warn!] and [error!] to the file whose name is
@@ -24,18 +74,587 @@ fn log_init() {
     log::set_max_level(LevelFilter::Info);
 }
 
-/// Modularization of the function responsible for opening the generated url in the system's default browser.
-fn open_browser(engine: &Engine, term: &str) {
-    match engine.url(term) {
-        Ok(url) => {
-            if open::that(url.clone()).is_ok() {
-                info!("Browser opened successfully. Url: {}", url);
-            } else {
-                error!("Error opening browser.");
+/// Reduces an engine name to the form used in comparisons, so that names differing only in casing or surrounding
+/// whitespace are considered the same
+fn normalize_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+
+/// Reasons why a name passed by the user could not be resolved to a single search engine
+#[derive(Debug)]
+enum LookupError {
+    /// No engine is referred to by the name, not even by prefix, holding the closest names as suggestions
+    NotFound(String, Vec<String>),
+
+    /// More than one engine starts with the name, holding the name of each candidate
+    Ambiguous(String, Vec<String>),
+}
+
+
+impl fmt::Display for LookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LookupError::NotFound(name, suggestions) => {
+                write!(f, "Engine '{}' not found", name)?;
+                if !suggestions.is_empty() {
+                    let quoted: Vec<String> = suggestions.iter().map(|suggestion| format!("'{}'", suggestion)).collect();
+                    write!(f, " — did you mean {}?", quoted.join(" or "))?;
+                }
+                Ok(())
+            }
+            LookupError::Ambiguous(name, candidates) => {
+                write!(f, "Engine '{}' is ambiguous, it could refer to:", name)?;
+                for candidate in candidates {
+                    write!(f, "\n  - {}", candidate)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+
+impl std::error::Error for LookupError {}
+
+
+impl LookupError {
+    /// The error as printed under `--json`, see [print_error]
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            LookupError::NotFound(name, suggestions) => json!({"code": "engine_not_found", "engine": name, "suggest": suggestions}),
+            LookupError::Ambiguous(name, candidates) => json!({"code": "engine_ambiguous", "engine": name, "candidates": candidates}),
+        }
+    }
+}
+
+
+/// Prints the error on the standard error. Under `--json` it's printed as a JSON object whose `code` names the kind of
+/// error, such as `{"code":"engine_not_found","engine":"goggle","suggest":["google"]}`, the errors other than the
+/// lookups of engines having the snake case name of their [io::ErrorKind] as code and the text as `message`.
+fn print_error(e: &io::Error, json: bool) {
+    if !json {
+        eprintln!("{}", e);
+        return;
+    }
+
+    let value = match e.get_ref().and_then(|inner| inner.downcast_ref::<LookupError>()) {
+        Some(lookup) => lookup.to_json(),
+        None => {
+            let code = format!("{:?}", e.kind()).chars().enumerate().fold(String::new(), |mut code, (index, c)| {
+                if c.is_uppercase() && index > 0 {
+                    code.push('_');
+                }
+                code.push(c.to_ascii_lowercase());
+                code
+            });
+            json!({"code": code, "message": e.to_string()})
+        }
+    };
+    eprintln!("{}", value);
+}
+
+
+/// Arguments of `add` describing a single engine, which can't be given along with `--defaults` or `--preset`
+const ADD_ENGINE_ARGS: [&str; 14] = [
+    "name", "url_pattern", "pattern", "regex", "replacement", "force", "interactive", "dry_run", "sample", "bang",
+    "home_url", "exact_syntax", "query_prefix", "query_suffix",
+];
+
+
+/// Terminal browsers tried, in order, when the URL must be opened inside the terminal
+const TERMINAL_BROWSERS: [&str; 3] = ["w3m", "lynx", "browsh"];
+
+
+/// Options given on the command line or in the configuration that affect how a term is searched
+#[derive(Default)]
+struct SearchOptions {
+    /// Values of the variables of the url pattern
+    variables: BTreeMap<String, String>,
+
+    /// Whether the URL should be opened in a terminal browser, whatever the engine says
+    terminal_browser: bool,
+
+    /// History file in which the opened URLs are recorded
+    history_path: PathBuf,
+
+    /// Settings of the configuration
+    settings: Settings,
+
+    /// Whether printed URLs are written as OSC 8 hyperlinks
+    hyperlink: bool,
+
+    /// Whether the term is searched as an exact match, see [Engine::exact_term]
+    exact: bool,
+
+    /// Whether the searches are opened at the onion address of the engines, see [Engine::onion]
+    tor: bool,
+
+    /// Side of the screen the browser window should be placed at, when comparing engines
+    side: Option<&'static str>,
+}
+
+
+impl SearchOptions {
+    /// Options taken from the configuration, to be completed with the ones given on the command line
+    fn from_config(config: &Configuration, search_dir: &Path) -> SearchOptions {
+        SearchOptions {
+            history_path: search_dir.join("history.jsonl"),
+            settings: config.settings.clone(),
+            hyperlink: config.settings.hyperlinks(false),
+            ..SearchOptions::default()
+        }
+    }
+}
+
+
+/// Finds the first terminal browser installed, see [TERMINAL_BROWSERS]
+fn terminal_browser() -> Result<PathBuf, io::Error> {
+    TERMINAL_BROWSERS.iter()
+        .find_map(|browser| which::which(browser).ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No terminal browser found (w3m, lynx or browsh)"))
+}
+
+
+/// Opens the URL with the first terminal browser found, inside the current terminal, in the same way [open_file]
+/// opens files in the terminal editor
+fn open_terminal_browser(url: &str) -> Result<(), io::Error> {
+    let browser = terminal_browser()?;
+
+    info!("Opening {} with {:?}", url, browser);
+    Command::new(browser).arg(url).status().map(|_| ())
+}
+
+
+/// Opens the URL with the browser command of the configuration, in which `{url}` is replaced by the URL and `{side}`
+/// by the side of the screen the window should be placed at when comparing engines, empty otherwise. The command is
+/// split into words as a shell would, but it's not run through one.
+fn open_browser_command(template: &str, url: &str, side: &str) -> Result<(), io::Error> {
+    let words = shell_words::split(template).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut words = words.iter().map(|word| word.replace("{url}", url).replace("{side}", side));
+    let program = words.next().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Empty browser command"))?;
+
+    info!("Opening {} with {}", url, program);
+    Command::new(program).args(words).spawn().map(|_| ())
+}
+
+
+/// Whether there's no graphical session to open a browser in, as when connected to a server through SSH. Only
+/// meaningful where browsers depend on X11 or Wayland.
+fn headless() -> bool {
+    cfg!(all(unix, not(target_os = "macos")))
+        && std::env::var_os("DISPLAY").is_none()
+        && std::env::var_os("WAYLAND_DISPLAY").is_none()
+}
+
+
+/// Returns the text as an OSC 8 hyperlink to the URL if asked to, so that terminals supporting it make the text
+/// clickable, or the text itself otherwise
+fn link(text: &str, url: &str, hyperlink: bool) -> String {
+    if hyperlink {
+        format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+    } else {
+        text.to_string()
+    }
+}
+
+
+/// Prints the value as a single line of JSON, the output of every command under `--json`
+fn print_json<T: Serialize + ?Sized>(value: &T) {
+    match serde_json::to_string(value) {
+        Ok(json) => println!("{}", json),
+        Err(e) => {
+            error!("Unable to convert the output to JSON. Error: {}", e);
+            eprintln!("Unable to convert the output to JSON");
+        }
+    }
+}
+
+
+/// Shows the lines through the pager, `$PAGER` or, if it's not set, `less -FRX`, which quits right away when they
+/// fit the screen
+fn page(lines: &[String]) -> Result<(), io::Error> {
+    let command = std::env::var("PAGER").unwrap_or_else(|_| String::from("less -FRX"));
+    let words = shell_words::split(&command).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let (program, arguments) = words.split_first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "The pager command is empty"))?;
+
+    let mut pager = Command::new(program).args(arguments).stdin(Stdio::piped()).spawn()?;
+    if let Some(mut input) = pager.stdin.take() {
+        for line in lines {
+            // The pager stops reading when the user quits it before the end
+            if writeln!(input, "{}", line).is_err() {
+                break;
+            }
+        }
+    }
+    pager.wait().map(|_| ())
+}
+
+
+/// Prints the lines, through the pager if asked to when writing to a terminal, see [page]
+fn print_lines(lines: &[String], pager: bool) {
+    if pager && io::stdout().is_terminal() {
+        match page(lines) {
+            Ok(_) => return,
+            Err(e) => warn!("Unable to run the pager, printing the lines instead. Error: {}", e),
+        }
+    }
+    for line in lines {
+        println!("{}", line);
+    }
+}
+
+
+/// Prints the URL, as an OSC 8 hyperlink if asked to, see [link]
+fn print_url(url: &str, hyperlink: bool) {
+    println!("{}", link(url, url, hyperlink));
+}
+
+
+/// Opens the URL generated for the engine with the first opener of the settings that applies: the reused tab for
+/// engines with `reuse_tab`, a terminal browser when requested, the browser command of the settings if there's one,
+/// printing the URL when there's no graphical session, the terminal emulator when running inside kitty, or the
+/// default browser. Returns whether the URL was opened.
+fn open_url(engine: &Engine, url: &str, options: &SearchOptions, state: &mut State) -> bool {
+    let settings = &options.settings;
+    for opener in &settings.openers {
+        let result = match opener {
+            Opener::ReuseTab if engine.reuse_tab && !options.terminal_browser => {
+                match devtools::open(&settings.devtools_endpoint(), url, state.search_tab.as_deref()) {
+                    Ok(tab) => {
+                        info!("Url opened in the reused tab {}: {}", tab, url);
+                        state.search_tab = Some(tab);
+                        return true;
+                    }
+                    Err(e) => {
+                        warn!("Unable to reuse a browser tab, opening a new one. Error: {}", e);
+                        continue;
+                    }
+                }
+            }
+            Opener::Emulator => {
+                let Some(emulator) = Emulator::detect() else {
+                    continue;
+                };
+                let opened = if options.terminal_browser || engine.terminal {
+                    terminal_browser().and_then(|browser| emulator.split(&browser, url))
+                } else {
+                    emulator.open(url)
+                };
+                match opened {
+                    Ok(_) => {
+                        info!("Url opened through {:?}: {}", emulator, url);
+                        return true;
+                    }
+                    Err(e) => {
+                        warn!("Unable to open the URL through {:?}. Error: {}", emulator, e);
+                        continue;
+                    }
+                }
+            }
+            Opener::Terminal if options.terminal_browser || engine.terminal => {
+                open_terminal_browser(url).inspect(|_| info!("Terminal browser opened successfully. Url: {}", url))
+            }
+            Opener::Command if settings.browser_command.is_some() => {
+                let template = settings.browser_command.as_deref().unwrap_or_default();
+                open_browser_command(template, url, options.side.unwrap_or_default())
+                    .map_err(|e| io::Error::new(e.kind(), format!("Unable to run the browser command: {}", e)))
+            }
+            Opener::Print if headless() => {
+                info!("No graphical session found, printing the URL instead of opening it");
+                print_url(url, options.hyperlink);
+                Ok(())
+            }
+            Opener::System => open::that(url).inspect(|_| info!("Browser opened successfully. Url: {}", url)),
+            _ => continue,
+        };
+
+        return match result {
+            Ok(_) => true,
+            Err(e) => {
+                error!("Error opening {}. Error: {}", url, e);
+                eprintln!("{}", e);
+                settings.notify("Unable to open the search", &e.to_string());
+                false
+            }
+        };
+    }
+
+    error!("None of the openers could open {}", url);
+    eprintln!("None of the openers of the settings could open the URL");
+    settings.notify("Unable to open the search", "None of the openers of the settings could open the URL");
+    false
+}
+
+
+/// Modularization of the function responsible for opening the generated url, see [open_url]. Opened URLs are
+/// recorded in the history, if enabled, tagged with the active session. Searches through Tor, with `--tor` or
+/// engines with `via_tor`, are opened at the onion address of the engine, if it declares one.
+fn open_browser(engine: &Engine, term: &str, options: &SearchOptions, state: &mut State) {
+    let url = if options.tor || engine.via_tor {
+        bounded_url(&engine.onion(), term, options)
+    } else {
+        bounded_url(engine, term, options)
+    };
+    let url = match url {
+        Ok(url) => url,
+        Err(e) => {
+            error!("Unable to generate URL. Error: {}", e);
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    if !open_url(engine, &url, options, state) {
+        return;
+    }
+    if options.settings.history {
+        history::record(&options.history_path, &history::Entry {
+            timestamp: Local::now(),
+            engine: engine.name.clone(),
+            term: term.to_string(),
+            url,
+            session: state.session.clone(),
+        });
+    }
+}
+
+
+/// Searches the term with the engine, according to its kind, after preprocessing it. Terms prefixed with `man:`
+/// are always searched in the local manual pages, the prefix being looked for before the preprocessing, which
+/// could add a query prefix, quote the term or strip its colon.
+fn search(engine: &Engine, term: &str, options: &SearchOptions, state: &mut State) {
+    if let Some(page) = local::man_page(term) {
+        local::man(page);
+        return;
+    }
+
+    match engine.kind {
+        EngineKind::Web => open_browser(engine, term, options, state),
+        EngineKind::LocalMan => local::man(&preprocess::apply(term, engine, options)),
+        EngineKind::LocalGrep => local::grep(&preprocess::apply(term, engine, options), engine.root.as_deref()),
+    }
+}
+
+
+/// Generates the URL of the term for the engine, preprocessing the term as [search] does, keeping it within the
+/// `max_url_length` of the settings: longer URLs are either refused or generated again from the term truncated at a
+/// word boundary, with a warning, as the `long_urls` setting says. The term is truncated before being preprocessed,
+/// so that the query prefix and suffix of the engine and the quotes of `--exact` are kept.
+fn bounded_url(engine: &Engine, term: &str, options: &SearchOptions) -> Result<String, io::Error> {
+    let generate = |term: &str| engine.url_with(&preprocess::apply(term, engine, options), &options.variables);
+    let url = generate(term)?;
+    let max = options.settings.max_url_length;
+    let length = url.chars().count();
+    if max == 0 || length <= max {
+        return Ok(url);
+    }
+
+    let too_long = || io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("The URL generated by {} is {} characters long, over the maximum of {} (settings.max_url_length)", engine.name, length, max),
+    );
+    if options.settings.long_urls == LongUrls::Abort {
+        return Err(too_long());
+    }
+
+    let mut words: Vec<&str> = term.split_whitespace().collect();
+    while words.len() > 1 {
+        words.pop();
+        let truncated = words.join(" ");
+        let url = generate(&truncated)?;
+        if url.chars().count() <= max {
+            warn!("Term truncated to {} words to fit the URL within {} characters", words.len(), max);
+            eprintln!("The URL was over {} characters long, the term was truncated to: {}", max, truncated);
+            return Ok(url);
+        }
+    }
+    Err(too_long())
+}
+
+
+
+/// Lets the user pick one of the configured engines from a list
+fn pick_engine(config: &Configuration) -> Result<Engine, io::Error> {
+    let name = Select::new("Search engine:", config.names()).prompt().map_err(io::Error::other)?;
+    config.where_name(name)
+}
+
+
+/// Expands the macros of the terms typed on the command line, such as `%date%`, see [macros::expand]
+fn expand_macros(terms: Option<Vec<String>>) -> Option<Vec<String>> {
+    terms.map(|terms| terms.iter().map(|term| macros::expand(term)).collect())
+}
+
+
+/// Terms searched by [query]: the terms left once the bang is taken, or the selected text if there are none, joined
+/// into a single one if asked to and restricted to the site of the [QueryArgs]
+fn search_terms(config: &Configuration, query: &QueryArgs, mut terms: Vec<String>) -> Vec<String> {
+    if terms.is_empty() {
+        terms.push(get_text());
+    }
+    let multi_term = match (query.join, query.separate) {
+        (true, _) => MultiTerm::Join,
+        (_, true) => MultiTerm::Separate,
+        _ => config.settings.multi_term,
+    };
+    if multi_term == MultiTerm::Join && terms.len() > 1 {
+        terms = vec![terms.join(" ")];
+    }
+    if let Some(site) = &query.site {
+        terms = terms.into_iter().map(|term| format!("{} site:{}", term, site)).collect();
+    }
+    terms
+}
+
+
+/// Searches the terms, or the selected text if there are none, as asked by the [QueryArgs]. The engines are taken
+/// from the bang starting the terms, the group, the picked engine, the given engine or the default one, in this
+/// order. Macros are not expanded here: the callers expand the ones typed on the command line, see [expand_macros],
+/// never the selected or copied text, whose `%clip%` would otherwise send the clipboard to the engine. With `print`
+/// or `copy` the URLs are only printed or
+/// copied to the clipboard, without being opened, after being generated in parallel, in the order of the engines
+/// and terms. Either way the state is saved, so that `@rotate` moves on to the next engine and the cooldowns start.
+/// Fails, before searching anything, when the engines can't be resolved, leaving the caller to decide whether to
+/// exit or, as `watch` does, carry on.
+fn query(config: &Configuration, search_dir: &Path, mut query: QueryArgs) -> Result<(), io::Error> {
+    let mut state = State::load(search_dir.join("state.yaml"));
+    let mut terms = query.term.take().unwrap_or_default();
+
+    let bang = match (&query.engine, &query.group, query.pick) {
+        (None, None, false) => take_bang(config, &mut terms),
+        _ => None,
+    };
+
+    let engines = if let Some(engine) = bang {
+        Ok(vec![engine])
+    } else if let Some(group) = &query.group {
+        config.group(group)
+    } else if query.pick {
+        pick_engine(config).map(|engine| vec![engine])
+    } else if let Some(name) = query.engine.take() {
+        meta::resolve(config, &mut state, name).map(|engine| vec![engine])
+    } else {
+        config.default().map(|engine| vec![engine])
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "There is no defined default search engine"))
+    };
+
+    let engines = engines?;
+    let terms = search_terms(config, &query, terms);
+
+    let options = SearchOptions {
+        variables: query.variables.into_iter().collect(),
+        terminal_browser: query.terminal_browser,
+        hyperlink: config.settings.hyperlinks(query.hyperlink),
+        exact: query.exact,
+        tor: query.tor,
+        ..SearchOptions::from_config(config, search_dir)
+    };
+
+    if query.print || query.copy {
+        let searches: Vec<(&Engine, &String)> = engines.iter()
+            .filter(|engine| engine.kind.is_web())
+            .flat_map(|engine| terms.iter().map(move |term| (engine, term)))
+            .collect();
+        let urls: Vec<String> = searches.into_par_iter()
+            .map(|(engine, term)| bounded_url(engine, term, &options))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .filter_map(|url| url.inspect_err(|e| {
+                error!("Unable to generate URL. Error: {}", e);
+                print_error(e, query.json);
+            }).ok())
+            .collect();
+
+        if query.print {
+            for url in &urls {
+                print_url(url, options.hyperlink);
+            }
+        }
+        if query.copy {
+            match clipboard::copy(&urls.join("\n"), &options.settings) {
+                Ok(_) => info!("URLs copied to the clipboard"),
+                Err(e) => {
+                    error!("Unable to copy to the clipboard. Error: {}", e);
+                    eprintln!("Unable to copy to the clipboard: {}", e);
+                }
+            }
+        }
+        for engine in engines.iter().filter(|engine| engine.kind.is_web()) {
+            state.opened(engine);
+        }
+    } else {
+        let searches = engines.len() * terms.len();
+        let progress = if searches > 1 { progress::bar(searches as u64) } else { ProgressBar::hidden() };
+        for engine in &engines {
+            if state.cooling_down(engine) {
+                info!("Ignoring the search, {} is cooling down", engine.name);
+                progress.suspend(|| eprintln!("Ignoring repeated search with {}", engine.name));
+                progress.inc(terms.len() as u64);
+                continue;
+            }
+
+            progress.set_message(engine.name.clone());
+            for term in &terms {
+                progress.suspend(|| search(engine, term, &options, &mut state));
+                progress.inc(1);
             }
+            state.opened(engine);
         }
-        Err(_) => error!("Unable to generate URL"),
+        progress.finish_and_clear();
+    }
+
+    if let Err(e) = state.save() {
+        error!("Unable to save the state file. Error: {}", e);
+    }
+    Ok(())
+}
+
+
+/// Runs [query] for the command line, exiting with an error when the engines can't be resolved
+fn query_or_exit(config: &Configuration, search_dir: &Path, args: QueryArgs) {
+    let json = args.json;
+    if let Err(e) = query(config, search_dir, args) {
+        error!("Unable to resolve the search engine. Error: {}", e);
+        print_error(&e, json);
+        std::process::exit(1);
+    }
+}
+
+
+/// Parses a `NAME=VALUE` pair given to `--var`
+fn parse_variable(value: &str) -> Result<(String, String), String> {
+    value.split_once('=')
+        .map(|(name, value)| (name.trim().to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected NAME=VALUE, found {}", value))
+}
+
+
+/// Parses a locale such as `pt_BR` given to `init --locale`
+fn parse_locale(value: &str) -> Result<presets::Locale, String> {
+    presets::Locale::parse(value).ok_or_else(|| format!("expected a locale such as pt_BR or de, found {}", value))
+}
+
+
+/// Locale the built-in engines are localized for by `init`: the one given, or else the one of the system, offered to
+/// the user when it isn't the English of the built-in engines and the input is a terminal
+fn init_locale(locale: Option<presets::Locale>, no_locale: bool, json: bool) -> Option<presets::Locale> {
+    if no_locale {
+        return None;
     }
+    locale.or_else(|| {
+        let detected = presets::Locale::detect().filter(|locale| !locale.is_default())?;
+        let offer = !json && io::stdin().is_terminal();
+        let accepted = offer && Confirm::new(&format!(
+            "Your locale is {}. Use the engines localized for it (Wikipedia in its language, Google and Bing set to it and a local news engine)?",
+            detected,
+        ))
+            .with_default(true)
+            .prompt()
+            .unwrap_or(false);
+        accepted.then_some(detected)
+    })
 }
 
 
@@ -55,9 +674,220 @@ fn open_file(path: PathBuf, terminal: bool, snippet: &str) {
 }
 
 
+/// Adds the engine to the configuration, unless it looks identical to an engine already configured. In that case,
+/// its name is registered as an alias of the existing engine instead of creating a near-duplicate, which the user is
+/// asked to confirm when `interactive`. Bulk additions, such as `import`, and input that isn't a terminal are never
+/// asked, the alias being added. Returns whether the engine was added, failing if an engine is already configured
+/// under its name, see [Configuration::push].
+fn add_engine(config: &mut Configuration, engine: Engine, interactive: bool) -> Result<bool, io::Error> {
+    if let Some(similar) = config.find_similar(&engine).map(|similar| similar.name.clone()) {
+        eprintln!("The engine {} looks identical to '{}'", engine.name, similar);
+
+        let merge = if interactive && io::stdin().is_terminal() {
+            Confirm::new(format!("Add '{}' as an alias of '{}' instead?", engine.name, similar).as_str())
+                .with_default(true)
+                .prompt()
+        } else {
+            eprintln!("Adding '{}' as an alias of '{}' instead", engine.name, similar);
+            Ok(true)
+        };
+
+        match merge {
+            Ok(true) => {
+                config.add_alias(&similar, &engine.name)?;
+                info!("Added {} as an alias of {}", engine.name, similar);
+                return Ok(false);
+            }
+            Ok(false) => info!("Adding {} despite being similar to {}", engine.name, similar),
+            Err(e) => warn!("Unable to ask whether to merge {} into {}. Error: {}", engine.name, similar, e),
+        }
+    }
+
+    config.push(engine)?;
+    Ok(true)
+}
+
+
+/// Prints the names of the engines added, one per line or, under `--json`, as the `added` list of a JSON object
+fn print_added(names: &[String], json: bool) {
+    if json {
+        print_json(&json!({"added": names}));
+    } else {
+        for name in names {
+            println!("+ {}", name);
+        }
+    }
+}
+
+
+/// Prints the engine as yaml along with the URL it generates for the sample term, so that the regex, pattern and
+/// replacement can be verified before the engine is added
+fn preview_engine(engine: Engine, sample: &str) {
+    match engine.url(&engine.narrow(sample)) {
+        Ok(url) => println!("# Sample URL for \"{}\": {}", sample, url),
+        Err(e) => eprintln!("Unable to generate a sample URL. Error: {}", e),
+    }
+    print_engine_as_yaml(&engine);
+}
+
+
+/// Prints the URL every web engine generates for the term, after preprocessing it as a search would, without
+/// opening any of them, so the regexes and patterns of the engines can be checked side by side. With `json` they
+/// are printed as a list of objects holding the engine and either its URL or the error.
+fn print_matrix(config: &Configuration, term: &str, options: &SearchOptions, json: bool) {
+    let engines: Vec<&Engine> = config.engines.iter().filter(|engine| engine.kind.is_web()).collect();
+
+    if json {
+        let rows: Vec<_> = engines.iter()
+            .map(|engine| match bounded_url(engine, term, options) {
+                Ok(url) => json!({"engine": engine.name, "url": url}),
+                Err(e) => json!({"engine": engine.name, "error": e.to_string()}),
+            })
+            .collect();
+        print_json(&rows);
+        return;
+    }
+
+    let width = engines.iter().map(|engine| engine.name.chars().count()).max().unwrap_or_default();
+
+    for engine in engines {
+        match bounded_url(engine, term, options) {
+            Ok(url) => println!("{:<width$}  {}", engine.name, link(&url, &url, options.hyperlink)),
+            Err(e) => println!("{:<width$}  ✘ {}", engine.name, e),
+        }
+    }
+}
+
+
+/// Writes the content to the file passed as an argument or, if none is passed, to the standard output
+fn write_output(content: &str, output: Option<PathBuf>) {
+    match output {
+        Some(path) => match fs::write(&path, content) {
+            Ok(_) => info!("Content written to {:?}", path),
+            Err(e) => {
+                error!("Unable to write to {:?}. Error: {}", path, e);
+                eprintln!("Unable to write to {}", path.display());
+            }
+        },
+        None => print!("{}", content),
+    }
+}
+
+
+/// Prints a unified diff between the configuration and the reference passed as an argument. When comparing to
+/// the built-in engines, the engines configured under the same name lend their uuid, so only meaningful
+/// differences are shown. The diff is colored according to the `color` setting.
+fn print_diff(config: &Configuration, against: DiffReference, backup_directory: &Path) {
+    let (reference, label, current) = match against {
+        DiffReference::Backup => match backups::latest(backup_directory) {
+            Some(path) => match fs::read_to_string(&path) {
+                Ok(content) => (content, path.display().to_string(), fs::read_to_string(&config.file_path).unwrap_or_default()),
+                Err(e) => {
+                    error!("Unable to read the backup {:?}. Error: {}", path, e);
+                    eprintln!("Unable to read the backup {}", path.display());
+                    return;
+                }
+            },
+            None => {
+                eprintln!("There is no backup to compare against");
+                return;
+            }
+        },
+        DiffReference::Defaults => {
+            let engines = presets::defaults().into_iter().map(|mut engine| {
+                if let Some(existing) = config.engines.get(&engine.name) {
+                    engine.uuid = existing.uuid;
+                }
+                engine
+            }).collect();
+            let defaults = Configuration::new(config.file_path.clone(), Some(presets::DEFAULT_ENGINE.to_string()), engines);
+            (serde_yaml::to_string(&defaults).unwrap_or_default(), String::from("defaults"), serde_yaml::to_string(config).unwrap_or_default())
+        }
+    };
+
+    let diff = TextDiff::from_lines(&reference, &current);
+    let diff = diff.unified_diff().header(&label, &config.file_path.display().to_string()).to_string();
+    if !config.settings.color.enabled() {
+        print!("{}", diff);
+        return;
+    }
+    for line in diff.lines() {
+        match line.chars().next() {
+            Some('+') if !line.starts_with("+++") => println!("\x1b[32m{}\x1b[0m", line),
+            Some('-') if !line.starts_with("---") => println!("\x1b[31m{}\x1b[0m", line),
+            Some('@') => println!("\x1b[36m{}\x1b[0m", line),
+            _ => println!("{}", line),
+        }
+    }
+}
+
+
+/// Warns that the configuration file was modified outside the program, offering to show the differences from the
+/// latest backup. The current content is then accepted, so the warning is given only once per modification. With
+/// `json` the warning is a JSON object on the standard error, as the errors are, and nothing is asked.
+fn warn_tampered(config: &Configuration, backup_directory: &Path, json: bool) {
+    if json {
+        eprintln!("{}", json!({
+            "warning": "configuration_modified",
+            "message": "The configuration file was modified outside search since it was last saved",
+        }));
+    } else {
+        eprintln!("The configuration file was modified outside search since it was last saved.");
+        if !io::stdin().is_terminal() {
+            eprintln!("Run `search config diff` to compare it with the latest backup.");
+        } else if Confirm::new("Show the differences from the latest backup?").with_default(false).prompt().unwrap_or(false) {
+            print_diff(config, DiffReference::Backup, backup_directory);
+        }
+    }
+
+    if let Err(e) = config.store_checksum() {
+        error!("Unable to store the configuration checksum. Error: {}", e);
+    }
+}
+
+
+/// Prints the completion script for the shell. The names, aliases and bang keywords of the configured engines are
+/// offered as values of `--engine`, and the bang keywords as search terms, so the script must be generated again
+/// after the engines change.
+fn print_completions(config: &Configuration, shell: Shell) {
+    let mut keywords: Vec<String> = config.engines.iter().flat_map(|engine| engine.keywords().cloned()).collect();
+    keywords.sort();
+    keywords.dedup();
+    let bangs: Vec<String> = keywords.iter().map(|keyword| format!("!{}", keyword)).collect();
+    keywords.extend(meta::NAMES.map(String::from));
+
+    let mut command = Cli::command()
+        .mut_arg("engine", |arg| arg.value_parser(PossibleValuesParser::new(keywords.clone())))
+        .mut_arg("term", |arg| arg.value_parser(PossibleValuesParser::new(bangs.clone())))
+        .mut_subcommand("query", |query| query
+            .mut_arg("engine", |arg| arg.value_parser(PossibleValuesParser::new(keywords)))
+            .mut_arg("term", |arg| arg.value_parser(PossibleValuesParser::new(bangs))));
+    generate(shell, &mut command, "search", &mut io::stdout());
+}
+
+
+/// Takes the bang keyword from the start of the terms, either as a word of its own (`search !g rust`) or as the
+/// first word of the first term (`search '!g rust'`), returning the engine it refers to. The terms are left
+/// untouched if they don't start with a keyword of a configured engine.
+fn take_bang(config: &Configuration, terms: &mut Vec<String>) -> Option<Engine> {
+    let first = terms.first()?.trim_start();
+    let keyword = first.strip_prefix('!')?.split_whitespace().next()?;
+    let engine = config.engines.get(keyword)?.clone();
+    info!("Bang {} refers to {}", keyword, engine.name);
+
+    let rest = first[1 + keyword.len()..].trim_start().to_string();
+    if rest.is_empty() {
+        terms.remove(0);
+    } else {
+        terms[0] = rest;
+    }
+    Some(engine)
+}
+
+
 /// Modularization for printing the search engine in the terminal in yaml format.
-fn print_engine_as_yaml(engine: Engine) {
-    if let Ok(element_as_string) = serde_yaml::to_string(&engine) {
+fn print_engine_as_yaml(engine: &Engine) {
+    if let Ok(element_as_string) = serde_yaml::to_string(engine) {
         println!("{}", element_as_string);
     } else {
         error!("Error when trying to convert engine {} to yaml.", engine.name);
@@ -65,12 +895,37 @@ fn print_engine_as_yaml(engine: Engine) {
     }
 }
 
+/// Kinds of search engines, according to where the search takes place
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EngineKind {
+    /// The term is searched in the browser, through the generated URL
+    #[default]
+    Web,
+
+    /// The term is searched in the local manual pages
+    LocalMan,
+
+    /// The term is searched with ripgrep in the files under the root directory of the engine
+    LocalGrep,
+}
+
+
+impl EngineKind {
+    /// Whether the kind is the default one, so that it's omitted from the configuration file
+    fn is_web(&self) -> bool {
+        *self == EngineKind::Web
+    }
+}
+
+
 /// This class was created with the aim of representing a search engine.
 /// It makes use of the macros [Serialize], [Deserialize] and [Parser] so that it can be serialized and deserialized
 /// by serde \[feature= serde_yaml] and passed as arguments on the command line. This object contains the
 /// minimum settings for the system to function properly, regarding the search engine URL.
 #[derive(Serialize, Deserialize, Debug, Parser, Clone)]
 pub struct Engine {
+    #[serde(default = "Uuid::new_v4")]
     uuid: Uuid,
 
     /// Represent the name of the search engine
@@ -85,6 +940,118 @@ pub struct Engine {
     /// The regex that will be searched within the search term and replaced by replacement
     regex: String,
     replacement: String,
+
+    /// Alternative names by which the search engine can also be referred to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[arg(skip)]
+    aliases: Vec<String>,
+
+    /// Default values of the `{name}` variables of the url pattern, such as `{version}`, used when no value is
+    /// passed on the command line
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[arg(skip)]
+    variables: BTreeMap<String, String>,
+
+    /// Where the search takes place, in the browser by default
+    #[serde(default, skip_serializing_if = "EngineKind::is_web")]
+    #[arg(skip)]
+    kind: EngineKind,
+
+    /// Directory searched by the `local-grep` engines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    root: Option<PathBuf>,
+
+    /// Whether the URL is always opened in a terminal browser
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[arg(skip)]
+    terminal: bool,
+
+    /// Time, in seconds, during which repeated searches with the engine are ignored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    cooldown_secs: Option<u64>,
+
+    /// Whether the URL is opened in the same browser tab as the previous search, through the remote debugging
+    /// endpoint of the browser
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[arg(skip)]
+    reuse_tab: bool,
+
+    /// Keyword selecting the engine when a search starts with `!keyword`, as with DuckDuckGo bangs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    bang: Option<String>,
+
+    /// Whether non-ASCII terms are transliterated to ASCII, for engines that choke on them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[arg(skip)]
+    transliterate: bool,
+
+    /// Whether emoji are stripped from the term, overriding the setting of the configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    strip_emoji: Option<bool>,
+
+    /// Whether punctuation is stripped from the term, overriding the setting of the configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    strip_punctuation: Option<bool>,
+
+    /// How likely the engine is to be picked by `@random` compared to the others, 1 if null
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    weight: Option<u32>,
+
+    /// Landing page of the engine, opened by `home`. The scheme and host of the url pattern if null.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    home_url: Option<String>,
+
+    /// Text added before the term, such as `site:wiki.internal`, to narrow every search of the engine
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    query_prefix: Option<String>,
+
+    /// Text added after the term, such as `lang:rust`, to narrow every search of the engine
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    query_suffix: Option<String>,
+
+    /// Url pattern of the search suggestions of the engine, in the OpenSearch suggestions format, with the same
+    /// pattern as the url pattern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    suggest_url: Option<String>,
+
+    /// Headers, cookies and credentials of the requests made to the engine over the network, see [http::HttpOptions]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    http: Option<http::HttpOptions>,
+
+    /// Exact-match syntax of the engine used by `--exact`, in which `{term}` is replaced by the term. The term in
+    /// double quotes if null.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    exact: Option<String>,
+
+    /// Whether the requests made to the engine by the program, such as its suggestions, always go through Tor, and
+    /// its searches are opened at its `onion_url`, as with `--tor`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[arg(skip)]
+    via_tor: bool,
+
+    /// Url pattern of the engine at its onion address, with the same pattern as the url pattern, opened instead of it
+    /// when searching through Tor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    onion_url: Option<String>,
+
+    /// How the term is percent-encoded after the regex is applied: the writing of the spaces, the charset and the
+    /// part of the URL it goes in, see [encoding::Encoding]. The term is left to the browser to encode if null.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    encoding: Option<encoding::Encoding>,
 }
 
 
@@ -95,11 +1062,50 @@ impl Engine {
         info!("Creating a new engine.");
         Engine {
             uuid: Uuid::new_v4(),
-            name: String::from(name),
+            name: String::from(name.trim()),
             url_pattern: String::from(url_pattern),
             pattern: pattern.to_string(),
             regex: regex.to_string(),
             replacement: String::from(replacement),
+            aliases: vec![],
+            variables: BTreeMap::new(),
+            kind: EngineKind::Web,
+            root: None,
+            terminal: false,
+            cooldown_secs: None,
+            reuse_tab: false,
+            bang: None,
+            transliterate: false,
+            strip_emoji: None,
+            strip_punctuation: None,
+            weight: None,
+            home_url: None,
+            query_prefix: None,
+            query_suffix: None,
+            suggest_url: None,
+            http: None,
+            exact: None,
+            via_tor: false,
+            onion_url: None,
+            encoding: None,
+        }
+    }
+
+
+    /// Adds the query prefix and suffix of the engine around the term
+    pub fn narrow(&self, term: &str) -> String {
+        [self.query_prefix.as_deref(), Some(term), self.query_suffix.as_deref()].into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+
+    /// Wraps the term in the exact-match syntax of the engine, double quotes unless it declares another one
+    pub fn exact_term(&self, term: &str) -> String {
+        match &self.exact {
+            Some(syntax) => syntax.replace("{term}", term),
+            None => format!("\"{}\"", term),
         }
     }
 
@@ -121,40 +1127,145 @@ impl Engine {
         )
     }
 
+    /// Checks whether the engine is referred to by the name passed as an argument, either by its name, one of its
+    /// aliases or its bang keyword, ignoring casing and surrounding whitespace
+    pub fn answers_to(&self, name: &str) -> bool {
+        let name = normalize_name(name);
+        self.keywords().any(|keyword| normalize_name(keyword) == name)
+    }
+
+
+    /// Landing page of the engine: its `home_url` or, if it has none, the scheme and host of its url pattern
+    pub fn home(&self) -> String {
+        if let Some(home_url) = &self.home_url {
+            return home_url.clone();
+        }
+        let start = self.url_pattern.find("://").map_or(0, |index| index + 3);
+        match self.url_pattern[start..].find('/') {
+            Some(index) => self.url_pattern[..start + index].to_string(),
+            None => self.url_pattern.clone(),
+        }
+    }
+
+
+    /// First eight characters of the UUID, enough to tell the engines apart when typing
+    pub fn short_uuid(&self) -> String {
+        self.uuid.simple().to_string()[..8].to_string()
+    }
+
+
+    /// Every word the engine can be referred to by: its name, its aliases and its bang keyword
+    pub fn keywords(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.name).chain(self.aliases.iter()).chain(self.bang.iter())
+    }
+
+
+    /// Reduces the url pattern to its host and path, ignoring the scheme, the `www.` prefix and the query string,
+    /// so that engines pointing to the same place can be recognized even if written slightly differently
+    pub fn signature(&self) -> String {
+        let without_scheme = self.url_pattern.split_once("://").map_or(self.url_pattern.as_str(), |(_, rest)| rest);
+        let without_query = without_scheme.split(['?', '#']).next().unwrap_or_default();
+        let (host, path) = without_query.split_once('/').unwrap_or((without_query, ""));
+        let host = host.to_lowercase();
+        let path = if self.pattern.is_empty() { path.to_string() } else { path.replace(&self.pattern, "*") };
+
+        format!("{}/{}", host.strip_prefix("www.").unwrap_or(&host), path.trim_end_matches('/'))
+    }
+
+
     /// Generate the url based on the data already existing in the [Engine] object and based on the term passed
     /// as argument
     pub fn url(&self, term: &str) -> Result<String, io::Error> {
+        self.url_with(term, &BTreeMap::new())
+    }
+
+
+    /// Generate the url of the search suggestions of the term, from the `suggest_url` of the engine
+    pub fn suggest_url(&self, term: &str) -> Result<String, io::Error> {
+        match &self.suggest_url {
+            Some(suggest_url) => Engine { url_pattern: suggest_url.clone(), ..self.clone() }.url(term),
+            None => Err(io::Error::new(io::ErrorKind::Unsupported, format!("The engine {} has no suggest_url", self.name))),
+        }
+    }
+
+
+    /// The engine as searched through Tor, with its `onion_url` as the url pattern if it declares one
+    pub fn onion(&self) -> Engine {
+        match &self.onion_url {
+            Some(onion_url) => Engine { url_pattern: onion_url.clone(), ..self.clone() },
+            None => self.clone(),
+        }
+    }
+
+
+    /// Fills the `{name}` variables of the url pattern with the values passed as an argument, falling back to the
+    /// default values declared by the engine, and the `{date:FORMAT}` placeholders with the current date, see
+    /// [macros::fill_dates]
+    fn fill_variables(&self, variables: &BTreeMap<String, String>) -> String {
+        let mut url_pattern = self.url_pattern.clone();
+        for (name, value) in variables.iter().chain(self.variables.iter()) {
+            url_pattern = url_pattern.replace(format!("{{{}}}", name).as_str(), value);
+        }
+        macros::fill_dates(&url_pattern)
+    }
+
+
+    /// Generate the url as [Engine::url] does, filling the variables of the url pattern with the values passed as
+    /// an argument. Macros in the url pattern, such as `%date%`, are expanded as well, see [macros::expand]. The
+    /// term is percent-encoded as the `encoding` of the engine says, if it has one.
+    pub fn url_with(&self, term: &str, variables: &BTreeMap<String, String>) -> Result<String, io::Error> {
         info!("Generating a URL.");
 
-        match Regex::new(self.regex.as_str()) {
+        match compiled(self.regex.as_str()) {
             Ok(regex) => {
-                let treated_string = regex.replace_all(term, &self.replacement).to_string();
+                let mut treated_string = regex.replace_all(term, &self.replacement).to_string();
+                if let Some(encoding) = &self.encoding {
+                    treated_string = encoding.encode(&treated_string)?;
+                }
                 info!("Treated string");
-                match Regex::new(&regex::escape(self.pattern.as_str())) {
+                match compiled(&regex::escape(self.pattern.as_str())) {
                     Ok(pattern) => {
-                        let url = pattern.replace_all(self.url_pattern.as_str(), treated_string).to_string();
+                        let template = macros::expand(&self.fill_variables(variables));
+                        let url = pattern.replace_all(template.as_str(), treated_string).to_string();
                         info!("Url generated successfully: {}", url);
                         Ok(url)
                     }
                     Err(e) => {
                         error!("Unable to generate replacement pattern. Error: {}", e);
-                        Err(io::Error::new(io::ErrorKind::Other, e))
+                        Err(io::Error::other(e))
                     }
                 }
             }
             Err(e) => {
                 error!("Failed to generate replacement pattern. Error: {}", e);
-                Err(io::Error::new(io::ErrorKind::Other, e))
+                Err(io::Error::other(e))
             }
         }
     }
 }
 
 
+/// Regexes compiled so far, by source, see [compiled]
+static REGEXES: LazyLock<Mutex<HashMap<String, Regex>>> = LazyLock::new(Mutex::default);
+
+
+/// Compiles the regex, or reuses the one compiled before from the same source, since generating the URLs of many
+/// terms would otherwise compile the regex and pattern of the engine again for every one of them
+fn compiled(source: &str) -> Result<Regex, regex::Error> {
+    let mut regexes = REGEXES.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(regex) = regexes.get(source) {
+        return Ok(regex.clone());
+    }
+    let regex = Regex::new(source)?;
+    regexes.insert(source.to_string(), regex.clone());
+    Ok(regex)
+}
+
+
 /// Class created with the objective of storing all the configurations that the program supports.
 /// The [Configuration] class has the macros [Serialize] and [Deserialize], so that it can be serialized and
 /// deserialized by serde \[feature=serde_yaml], in order to be written to and read from a .yaml file
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct Configuration {
     /// Stores the configuration file path;
     #[serde(skip_serializing)]
@@ -164,8 +1275,37 @@ struct Configuration {
     /// Stores the name of the default search engine, null by default and subject to change, according to user preferences
     default_engine: Option<String>,
 
-    /// Stores all objects representing search engines - [Engine]
-    engines: Option<Vec<Engine>>,
+    /// Stores all objects representing search engines - [Engine], keyed by name
+    #[serde(default)]
+    engines: Engines,
+
+    /// Engines removed without `--purge`, hidden from every listing and lookup until restored
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    archived: Vec<Engine>,
+
+    /// Groups of engines searched together, by group name, each listing the names of its engines
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    groups: BTreeMap<String, Vec<String>>,
+
+    /// Directory in which the research summaries are written, ~/.search/notes if null
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notes_dir: Option<PathBuf>,
+
+    /// Names of the engines `@rotate` cycles through, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    rotation: Vec<String>,
+
+    /// Settings affecting how the searches behave, see [Settings]
+    #[serde(default)]
+    settings: Settings,
+
+    /// Whether the configuration was changed since it was loaded, so that it's only saved when needed
+    #[serde(skip)]
+    modified: bool,
+
+    /// Whether the file was modified outside the program since it was last saved
+    #[serde(skip)]
+    tampered: bool,
 }
 
 
@@ -175,12 +1315,19 @@ struct Configuration {
 /// to indicate to the compiler that there are no problems with the existence of _dead_ code, this directive is used
 impl Configuration {
     /// Responsible for creating a new instance of a configuration object based on the values passed as arguments
-    pub fn new(file_path: PathBuf, default_engine: Option<String>, engines: Option<Vec<Engine>>) -> Configuration {
+    pub fn new(file_path: PathBuf, default_engine: Option<String>, engines: Vec<Engine>) -> Configuration {
         info!("Creating a new settings.");
         Configuration {
             file_path,
             default_engine,
-            engines,
+            engines: engines.into_iter().collect(),
+            archived: vec![],
+            groups: BTreeMap::new(),
+            notes_dir: None,
+            rotation: vec![],
+            settings: Settings::default(),
+            modified: false,
+            tampered: false,
         }
     }
 
@@ -197,7 +1344,7 @@ impl Configuration {
             match File::create(file_path.clone()) {
                 Ok(_) => {
                     info!("Success creating configuration file");
-                    Ok(Configuration::new(file_path, None, None))
+                    Ok(Configuration::new(file_path, None, vec![]))
                 }
                 Err(e) => {
                     error!("Error creating file. Error: {}", e);
@@ -206,14 +1353,17 @@ impl Configuration {
             }
         } else if fs::metadata(file_path.clone()).map(|metadata| metadata.len() == 0).unwrap_or(true) {
             info!("The config file is empty");
-            Ok(Configuration::new(file_path, None, None))
+            let mut config = Configuration::new(file_path, None, vec![]);
+            config.tampered = config.checksum_mismatch();
+            Ok(config)
         } else {
-            match File::open(file_path.clone()) {
-                Ok(file) => {
-                    match serde_yaml::from_reader::<File, Configuration>(file) {
+            match fs::read_to_string(&file_path) {
+                Ok(content) => {
+                    match serde_yaml::from_str::<Configuration>(&content) {
                         Ok(mut config) => {
                             info!("Settings loaded successfully");
                             config.update_path(file_path);
+                            config.tampered = config.checksum_mismatch();
                             Ok(config)
                         }
                         Err(error) => {
@@ -231,17 +1381,18 @@ impl Configuration {
     }
 
 
-    /// Saves the object contents to a .yaml file
+    /// Saves the object contents to a .yaml file, streamed through a buffer rather than built in memory first
     pub fn save(&self) -> Result<(), io::Error> {
         info!("Trying to save to file {:?}", self.file_path);
         match File::create(self.file_path.clone()) {
-            Ok(mut file) => {
-                match serde_yaml::to_writer(&file, &self) {
+            Ok(file) => {
+                let mut writer = BufWriter::new(file);
+                match serde_yaml::to_writer(&mut writer, &self) {
                     Ok(_) => {
-                        match file.flush() {
+                        match writer.flush() {
                             Ok(_) => {
                                 info!("Configuration saved successfully");
-                                Ok(())
+                                self.store_checksum()
                             }
                             Err(e) => {
                                 error!("Error saving file: {}", e);
@@ -251,7 +1402,7 @@ impl Configuration {
                     }
                     Err(e) => {
                         error!("Error writing file. Message: {}", e);
-                        Err(io::Error::new(io::ErrorKind::Other, e))
+                        Err(io::Error::other(e))
                     }
                 }
             }
@@ -263,175 +1414,946 @@ impl Configuration {
     }
 
 
-    /// Adds an engine to the list of configured search engines
-    pub fn push(&mut self, engine: Engine) {
-        self.engines = self.engines.clone().map_or(Some(vec![engine.clone()]), |mut vector| {
-            vector.push(engine);
-            Some(vector)
-        });
-    }
+    /// Path of the sidecar file storing the checksum of the configuration file as last saved
+    fn checksum_path(&self) -> PathBuf {
+        self.file_path.with_extension("yaml.sha256")
+    }
+
+
+    /// Computes the checksum of the configuration file as it currently is
+    fn file_checksum(&self) -> Result<String, io::Error> {
+        Ok(format!("{:x}", Sha256::digest(fs::read(&self.file_path)?)))
+    }
+
+
+    /// Stores the checksum of the configuration file in the sidecar file, so that changes made outside the program
+    /// can be noticed on the next load
+    pub fn store_checksum(&self) -> Result<(), io::Error> {
+        fs::write(self.checksum_path(), self.file_checksum()?)
+    }
+
+
+    /// Checks whether the configuration file differs from the checksum stored when it was last saved. Files
+    /// without a stored checksum are never considered modified.
+    fn checksum_mismatch(&self) -> bool {
+        match fs::read_to_string(self.checksum_path()) {
+            Ok(stored) => match self.file_checksum() {
+                Ok(current) => {
+                    let mismatch = stored.trim() != current;
+                    if mismatch {
+                        warn!("The configuration file was modified outside the program since it was last saved");
+                    }
+                    mismatch
+                }
+                Err(e) => {
+                    error!("Unable to compute the configuration checksum. Error: {}", e);
+                    false
+                }
+            },
+            Err(_) => false,
+        }
+    }
+
+
+    /// Adds an engine to the configured search engines, failing if one is already configured under the same name.
+    /// [Configuration::replace] replaces it instead.
+    pub fn push(&mut self, engine: Engine) -> Result<(), io::Error> {
+        if self.engines.named(&engine.name).is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("The config file already contains a search engine named {}", engine.name),
+            ));
+        }
+        self.replace(engine);
+        Ok(())
+    }
+
+
+    /// Adds an engine to the configured search engines, replacing the one configured under the same name
+    pub fn replace(&mut self, engine: Engine) {
+        if let Some(previous) = self.engines.insert(engine) {
+            info!("Replaced the engine {}", previous.name);
+        }
+        self.modified = true;
+    }
+
+
+    /// Adds the engines passed as an argument, skipping the ones whose name is already configured and dropping
+    /// the aliases already in use. Returns the names of the engines added.
+    pub fn merge(&mut self, engines: Vec<Engine>) -> Vec<String> {
+        let mut added = vec![];
+        for mut engine in engines {
+            if self.contains(&engine.name) {
+                info!("Skipping {}, already configured", engine.name);
+            } else {
+                engine.aliases.retain(|alias| !self.contains(alias));
+                added.push(engine.name.clone());
+                self.replace(engine);
+            }
+        }
+        added
+    }
+
+
+    /// Replaces every configured engine, and the default one, by the engines passed as an argument, keeping the
+    /// settings, groups, rotation and archive
+    pub fn reset_engines(&mut self, engines: Vec<Engine>) {
+        self.engines = Engines::default();
+        self.default_engine = None;
+        self.merge(engines);
+        self.modified = true;
+    }
+
+
+    /// Copies the configuration file, as currently saved, to the backup directory passed as an argument,
+    /// returning the path of the copy. Only the latest [backups::KEEP] backups are kept.
+    pub fn backup(&self, directory: &Path) -> Result<PathBuf, io::Error> {
+        if !directory.exists() {
+            create_dir(directory)?;
+        }
+        let path = directory.join(format!("search_config-{}.yaml", Local::now().format("%Y%m%d%H%M%S")));
+        fs::copy(&self.file_path, &path)?;
+        info!("Configuration backed up to {:?}", path);
+        backups::prune(directory);
+        Ok(path)
+    }
+
+
+    /// Adds an alias to the engine whose name is passed as an argument
+    pub fn add_alias(&mut self, name: &str, alias: &str) -> Result<(), io::Error> {
+        let added = self.engines.modify(name, |engine| {
+            let added = !engine.answers_to(alias);
+            if added {
+                engine.aliases.push(alias.trim().to_string());
+            }
+            added
+        });
+
+        match added {
+            Some(added) => {
+                self.modified |= added;
+                Ok(())
+            }
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid engine name")),
+        }
+    }
+
+
+    /// Changes the optional fields of the engine referred to by the name passed as an argument, see [EngineExtras]
+    fn edit(&mut self, name: String, extras: EngineExtras) -> Result<(), io::Error> {
+        let engine = self.where_name(name)?;
+        self.engines.modify(&engine.name, |engine| extras.apply(engine));
+        self.modified = true;
+        Ok(())
+    }
+
+
+    /// Returns the configured engine whose url pattern points to the same host and path as the one passed as an
+    /// argument, if any
+    pub fn find_similar(&self, engine: &Engine) -> Option<&Engine> {
+        let signature = engine.signature();
+        self.engines.iter().find(|element| element.signature() == signature)
+    }
+
+
+    /// Updates the file path
+    pub fn update_path(&mut self, new: PathBuf) {
+        self.file_path = new;
+    }
+
+
+    /// Removes a search engine based on name, or on an unambiguous prefix of it, moving it to the archive unless
+    /// `purge` is given, see [Self::archive]
+    pub fn remove_where_name(&mut self, name: &str, purge: bool) -> Result<String, io::Error> {
+        let engine = self.where_name(name.to_string())?;
+        self.engines.remove(&engine.name);
+        Ok(self.archive(engine, purge))
+    }
+
+
+    /// Allows an engine to be removed based on UUID, or on an unambiguous prefix of it, moving it to the archive
+    /// unless `purge` is given, see [Self::archive]
+    pub fn remove_where_uuid(&mut self, uuid: &str, purge: bool) -> Result<String, io::Error> {
+        let engine = self.where_uuid(uuid)?;
+        self.engines.retain(|element| element.uuid != engine.uuid);
+        Ok(self.archive(engine, purge))
+    }
+
+
+    /// Keeps the removed engine in the archive, from which it can be restored, replacing an archived engine of the
+    /// same name. Purged engines are dropped for good. Returns the name of the engine.
+    fn archive(&mut self, engine: Engine, purge: bool) -> String {
+        let name = engine.name.clone();
+        if purge {
+            info!("Engine {} purged", engine.name);
+        } else {
+            info!("Engine {} archived", engine.name);
+            self.archived.retain(|archived| normalize_name(&archived.name) != normalize_name(&engine.name));
+            self.archived.push(engine);
+        }
+        self.modified = true;
+        name
+    }
+
+
+    /// Moves the archived engine whose name is passed as an argument back to the configured search engines. Fails
+    /// if another engine was configured under its name in the meantime.
+    pub fn restore(&mut self, name: &str) -> Result<(), io::Error> {
+        let position = self.archived.iter().position(|engine| normalize_name(&engine.name) == normalize_name(name))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("No archived engine named '{}'", name.trim())))?;
+        if self.contains(&self.archived[position].name) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("An engine named '{}' is already configured", name.trim())));
+        }
+
+        let engine = self.archived.remove(position);
+        info!("Engine {} restored", engine.name);
+        self.push(engine)
+    }
+
+
+    /// Returns the search engine whose UUID starts with the value passed as an argument, which must match the UUID
+    /// of a single engine. Hyphens and casing are ignored, so both `3fa85f64` and `3FA8-5F64` are accepted.
+    pub fn where_uuid(&self, prefix: &str) -> Result<Engine, io::Error> {
+        let simplified = prefix.trim().replace('-', "").to_lowercase();
+        let candidates: Vec<&Engine> = self.engines.iter()
+            .filter(|engine| !simplified.is_empty() && engine.uuid.simple().to_string().starts_with(&simplified))
+            .collect();
+
+        match candidates.as_slice() {
+            [engine] => Ok((*engine).clone()),
+            [] => Err(io::Error::new(io::ErrorKind::NotFound, LookupError::NotFound(prefix.to_string(), vec![]))),
+            _ => {
+                let names = candidates.iter().map(|engine| format!("{} ({})", engine.name, engine.short_uuid())).collect();
+                Err(io::Error::new(io::ErrorKind::InvalidInput, LookupError::Ambiguous(prefix.to_string(), names)))
+            }
+        }
+    }
+
+
+    /// Generates a list of the names of the configured search engines
+    pub fn names(&self) -> Vec<String> {
+        self.engines.iter().map(|element| element.name.clone()).collect()
+    }
+
+
+    /// Returns the default search engine
+    pub fn default(&self) -> Option<Engine> {
+        self.engines.get(self.default_engine.as_ref()?).cloned()
+    }
+
+
+    /// Checks whether an engine is referred to by the name passed as an argument
+    pub fn contains(&self, name: &str) -> bool {
+        self.engines.get(name).is_some()
+    }
+
+
+    /// Sets the default search engine based on name, storing the name as written in the engine itself
+    pub fn set_default(&mut self, name: String) -> Result<(), io::Error> {
+        let engine = self.where_name(name)?;
+        if self.default_engine.as_ref() != Some(&engine.name) {
+            self.default_engine = Some(engine.name);
+            self.modified = true;
+        }
+        Ok(())
+    }
+
+
+    /// Returns the engines of the group passed as an argument, resolving each of its names with [Self::where_name]
+    pub fn group(&self, name: &str) -> Result<Vec<Engine>, io::Error> {
+        let members = self.groups.iter()
+            .find(|(group, _)| normalize_name(group) == normalize_name(name))
+            .map(|(_, members)| members)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("Group '{}' not found", name.trim())))?;
+
+        members.iter().map(|member| self.where_name(member.clone())).collect()
+    }
+
+
+    /// Directory in which the research summaries are written
+    pub fn notes_dir(&self) -> PathBuf {
+        match &self.notes_dir {
+            Some(directory) => directory.clone(),
+            None => self.file_path.with_file_name("notes"),
+        }
+    }
+
+
+    /// Returns up to three configured names or aliases close enough to the name passed as an argument, by edit
+    /// distance, to be suggested when it does not resolve to any engine
+    pub fn suggestions(&self, name: &str) -> Vec<String> {
+        let name = normalize_name(name);
+        let threshold = (name.chars().count() / 3).max(2);
+
+        let mut candidates: Vec<(usize, String)> = self.engines.iter()
+            .flat_map(|engine| std::iter::once(&engine.name).chain(engine.aliases.iter()))
+            .map(|value| (levenshtein(&normalize_name(value), &name), value.clone()))
+            .filter(|(distance, _)| *distance <= threshold)
+            .collect();
+
+        candidates.sort();
+        candidates.dedup_by(|a, b| a.1 == b.1);
+        candidates.into_iter().take(3).map(|(_, value)| value).collect()
+    }
+
+
+    /// Returns the search engine based on the name passed as an argument. If no engine is referred to by exactly
+    /// that name, it is taken as a prefix, which must match the name or an alias of a single engine. The errors
+    /// wrap a [LookupError] describing why the name could not be resolved.
+    pub fn where_name(&self, name: String) -> Result<Engine, io::Error> {
+        if let Some(engine) = self.engines.get(&name) {
+            return Ok(engine.clone());
+        }
+
+        let prefix = normalize_name(&name);
+        let candidates: Vec<&Engine> = self.engines.iter()
+            .filter(|engine| std::iter::once(&engine.name).chain(engine.aliases.iter())
+                .any(|value| normalize_name(value).starts_with(&prefix)))
+            .collect();
+
+        match candidates.as_slice() {
+            [engine] => {
+                info!("Resolved {} to {}", name, engine.name);
+                Ok((*engine).clone())
+            }
+            [] => {
+                let suggestions = self.suggestions(&name);
+                Err(io::Error::new(io::ErrorKind::NotFound, LookupError::NotFound(name, suggestions)))
+            }
+            _ => {
+                let names = candidates.iter().map(|engine| engine.name.clone()).collect();
+                Err(io::Error::new(io::ErrorKind::InvalidInput, LookupError::Ambiguous(name, names)))
+            }
+        }
+    }
+}
+
+
+/// Class responsible for intermediate the command line with the executable.
+/// [Parser], belonging to *Clap*, is used to generate the implementation for the command line.
+/// command macros are used to add information to the command line, according to their name.
+#[derive(Parser)]
+#[command(author = "Arthur Valadares Campideli", version, about = "An application to open a search term from the command line", long_about = "This application was created with the aim of adding a shortcut to the keyboard in order to search the selected text", subcommand_negates_reqs = true)]
+#[command(propagate_version = true)]
+struct Cli {
+    /// Optional argument. If none is specified, the default will be used
+    #[arg(long, short, help = "Specifies the search engine to be used")]
+    engine: Option<String>,
+
+    /// Value of the `{version}` variable of the url pattern, used by documentation engines
+    #[arg(long, visible_alias = "crate-version", help = "Version of the documentation to search")]
+    doc_version: Option<String>,
+
+    /// Opens the URL inside the terminal, for SSH sessions and TTY-only setups
+    #[arg(long, help = "Open the URL in a terminal browser (w3m, lynx or browsh)")]
+    terminal_browser: bool,
+
+    /// Several terms are searched as a single query, or each on its own, whatever the settings say
+    #[arg(long, conflicts_with = "separate", help = "Search the terms joined into a single query")]
+    join: bool,
+
+    #[arg(long, help = "Search each term on its own")]
+    separate: bool,
+
+    /// The term is searched as an exact match, without quoting it for the shell
+    #[arg(long, help = "Search the term as an exact match, in double quotes or the syntax of the engine")]
+    exact: bool,
+
+    /// Printed URLs are written as OSC 8 hyperlinks, whatever the settings say
+    #[arg(long, global = true, help = "Print the URLs as clickable OSC 8 hyperlinks")]
+    hyperlink: bool,
+
+    /// Requests go through the Tor proxy of the settings and searches are opened at the onion address of the engines
+    #[arg(long, global = true, help = "Route the requests through Tor and open the onion address of the engines")]
+    tor: bool,
+
+    /// Results are printed as JSON, for other programs to read
+    #[arg(long, global = true, help = "Print the results as JSON")]
+    json: bool,
+
+    /// Progress bars and spinners are never shown, as when the output isn't a terminal or under `--json`
+    #[arg(long, global = true, help = "Don't show progress bars")]
+    plain: bool,
+
+    /// Read-only mode, the configuration file is never written
+    #[arg(long, global = true, help = "Don't save the configuration, even if the command changed it")]
+    no_save: bool,
+
+    /// Commands that can be executed
+    #[command(subcommand)]
+    commands: Option<Commands>,
+
+    /// The search term to be used, possibly null, in this case the selected text will be used
+    #[arg(num_args(0..), help = "Specify the term to be searched for")]
+    term: Option<Vec<String>>,
+}
+
+
+/// Optional fields of an engine that can be given when adding it or changed with [Commands::Edit]. Empty values
+/// unset the field.
+#[derive(Args, Default)]
+struct EngineExtras {
+    #[arg(long, help = "Keyword selecting the engine when a search starts with !BANG")]
+    bang: Option<String>,
+
+    #[arg(long, help = "Landing page of the engine, opened by the home subcommand")]
+    home_url: Option<String>,
+
+    #[arg(long, value_name = "SYNTAX", help = "Exact-match syntax of the engine used by --exact, with {term} replaced by the term")]
+    exact_syntax: Option<String>,
+
+    #[arg(long, value_name = "TEXT", help = "Text added before every term searched with the engine, such as site:wiki.internal")]
+    query_prefix: Option<String>,
+
+    #[arg(long, value_name = "TEXT", help = "Text added after every term searched with the engine, such as lang:rust")]
+    query_suffix: Option<String>,
+}
+
+
+impl EngineExtras {
+    /// Whether no field is given
+    fn is_empty(&self) -> bool {
+        [&self.bang, &self.home_url, &self.exact_syntax, &self.query_prefix, &self.query_suffix].iter().all(|field| field.is_none())
+    }
+
+
+    /// Sets the given fields of the engine, unsetting the ones given empty
+    fn apply(self, engine: &mut Engine) {
+        let fields = [
+            (self.bang, &mut engine.bang),
+            (self.home_url, &mut engine.home_url),
+            (self.exact_syntax, &mut engine.exact),
+            (self.query_prefix, &mut engine.query_prefix),
+            (self.query_suffix, &mut engine.query_suffix),
+        ];
+        for (value, field) in fields {
+            if let Some(value) = value {
+                *field = Some(value).filter(|value| !value.trim().is_empty());
+            }
+        }
+    }
+}
+
+
+/// Options of a search, taken by the [Commands::Query] subcommand. The most common ones can be given directly to
+/// the program as well, `search -e google term` being the same as `search query -e google term`.
+#[derive(Args, Default)]
+struct QueryArgs {
+    #[arg(long, short, help = "Specifies the search engine to be used")]
+    engine: Option<String>,
+
+    #[arg(long, short, conflicts_with_all = ["engine", "pick"], help = "Search with every engine of the group")]
+    group: Option<String>,
+
+    #[arg(long, conflicts_with = "engine", help = "Pick the search engine from a list")]
+    pick: bool,
+
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable, help = "Value of a variable of the url pattern")]
+    variables: Vec<(String, String)>,
+
+    #[arg(long, value_name = "DOMAIN", help = "Restrict the search to the domain, appending site:DOMAIN to the term")]
+    site: Option<String>,
+
+    #[arg(long, help = "Print the URL instead of opening it")]
+    print: bool,
+
+    #[arg(long, help = "Copy the URL to the clipboard instead of opening it")]
+    copy: bool,
+
+    #[arg(long, help = "Open the URL in a terminal browser (w3m, lynx or browsh)")]
+    terminal_browser: bool,
+
+    #[arg(long, conflicts_with = "separate", help = "Search the terms joined into a single query")]
+    join: bool,
+
+    #[arg(long, help = "Search each term on its own")]
+    separate: bool,
+
+    #[arg(long, help = "Search the term as an exact match, in double quotes or the syntax of the engine")]
+    exact: bool,
+
+    /// Taken from the global `--hyperlink` flag
+    #[arg(skip)]
+    hyperlink: bool,
+
+    /// Taken from the global `--tor` flag
+    #[arg(skip)]
+    tor: bool,
+
+    /// Taken from the global `--json` flag
+    #[arg(skip)]
+    json: bool,
+
+    #[arg(num_args(0..), help = "Specify the term to be searched for")]
+    term: Option<Vec<String>>,
+}
+
+
+/// Enum containing the subcommands that can be executed from the Cli.
+#[derive(Subcommand)]
+enum Commands {
+    /// Searches a term, with every search option available
+    #[clap(about = "Search a term, with every search option available")]
+    Query(QueryArgs),
+
+    /// Lists the configured search engines
+    #[clap(about = "List configured search engines")]
+    List {
+        #[arg(short, long, help = "Show the uuid prefix and the url pattern of each engine")]
+        long: bool,
+
+        #[arg(long, help = "Show at most LIMIT engines")]
+        limit: Option<usize>,
+
+        #[arg(long, default_value_t = 0, help = "Skip the first OFFSET engines")]
+        offset: usize,
+
+        #[arg(long, help = "Show the list through the pager, $PAGER or less")]
+        pager: bool,
+    },
+
+    /// Defines and shows the default search engine configured
+    #[clap(about = "Show the default search engine")]
+    Default,
+
+    #[clap(about = "Set the default search engine")]
+    SetDefault { name: String },
+
+    /// Adds a search engine based on the values requested by [Engine::new]
+    #[clap(about = "Add a search engine")]
+    Add {
+        #[arg(help = "Search engine name", required_unless_present_any = ["interactive", "defaults", "preset"])]
+        name: Option<String>,
+
+        #[arg(help = "Search engine url pattern", required_unless_present_any = ["interactive", "defaults", "preset"])]
+        url_pattern: Option<String>,
+
+        #[arg(help = "Pattern that will be replaced by the treated search term", required_unless_present_any = ["interactive", "defaults", "preset"])]
+        pattern: Option<String>,
+
+        #[arg(help = "Regex that will be applied to the search term", required_unless_present_any = ["interactive", "defaults", "preset"])]
+        regex: Option<String>,
+
+        #[arg(help = "Value by which the regex will be replaced", required_unless_present_any = ["interactive", "defaults", "preset"])]
+        replacement: Option<String>,
+
+        #[arg(short, long, help = "Replace the search engine configured under the same name")]
+        force: bool,
+
+        #[arg(short, long, help = "Adds a new search engine interactively")]
+        interactive: bool,
+
+        #[arg(long, help = "Show the resulting engine and a sample URL without adding it")]
+        dry_run: bool,
+
+        #[arg(long, default_value = "hello world", requires = "dry_run", help = "Term used to generate the sample URL")]
+        sample: String,
+
+        #[command(flatten)]
+        extras: EngineExtras,
+
+        #[arg(long, conflicts_with_all = ADD_ENGINE_ARGS, conflicts_with = "preset", help = "Add the built-in engines, skipping the names already configured")]
+        defaults: bool,
+
+        #[arg(long, conflicts_with_all = ADD_ENGINE_ARGS, value_parser = PossibleValuesParser::new(presets::PRESETS.map(|(name, _)| name)), help = "Add the engines of a preset, skipping the names already configured")]
+        preset: Option<String>,
+    },
+
+    /// Changes the optional fields of a search engine
+    #[clap(about = "Change the optional fields of a search engine, an empty value unsetting the field")]
+    Edit {
+        #[arg(help = "Search engine name")]
+        name: String,
+
+        #[command(flatten)]
+        extras: EngineExtras,
+    },
+
+    /// Applies engine definitions and operations in bulk
+    #[clap(about = "Apply a YAML or JSON list of engine definitions and operations to the configuration, all or nothing")]
+    Apply {
+        #[arg(help = "File to read the list from, - for the standard input")]
+        source: String,
+    },
+
+    /// Seeds the configuration with the built-in engines
+    #[clap(about = "Initialize the configuration with the built-in engines")]
+    Init {
+        #[arg(long, value_name = "LOCALE", value_parser = parse_locale, conflicts_with = "no_locale", help = "Localize the built-in engines for the locale, as pt_BR, instead of the one of the system")]
+        locale: Option<presets::Locale>,
+
+        #[arg(long, help = "Keep the built-in engines in English, without offering the ones localized for the system")]
+        no_locale: bool,
+    },
+
+    /// Guides new users through adding an engine
+    #[clap(about = "Learn step by step how to add a search engine, make it the default one and bind a hotkey")]
+    Tutorial,
+
+    /// Replaces the configuration by the built-in engines, after backing it up
+    #[clap(about = "Reset the configuration to the built-in engines")]
+    Reset {
+        #[arg(short, long, help = "Don't ask for confirmation")]
+        yes: bool,
+    },
+
+    /// Removes a search engine based on name
+    #[clap(about = "Remove a search engine based on name or uuid")]
+    Remove {
+        value: String,
+
+        #[arg(short, long, help = "Take the value as a uuid, or an unambiguous prefix of one")]
+        uuid: bool,
+
+        #[arg(long, help = "Delete the engine for good instead of archiving it")]
+        purge: bool,
+    },
+
+    /// Manages the favicons shown by `list`
+    #[clap(about = "Download the favicons of the search engines shown by list")]
+    Favicons {
+        #[command(subcommand)]
+        command: FaviconsCommands,
+    },
+
+    /// Manages the engines removed without `--purge`
+    #[clap(about = "List and restore the removed search engines")]
+    Archive {
+        #[command(subcommand)]
+        command: ArchiveCommands,
+    },
+
+    #[clap(about = "Shows a specific search engine or all")]
+    Show {
+        name: Option<String>,
+
+        #[arg(short, long, required_unless_present = "name")]
+        all: bool,
+
+        #[arg(short, long, requires = "name", help = "Take the name as a uuid, or an unambiguous prefix of one")]
+        uuid: bool,
+    },
+
+    #[clap(about = "Open the file containing the settings")]
+    Open {
+        #[arg(short, long, help = "Open the file in the system's default terminal editor")]
+        terminal: bool
+    },
+
+    /// Reports the health of the configured search engines
+    #[clap(about = "Check the health of the configured search engines")]
+    Doctor {
+        #[arg(short, long, help = "Request each engine host with a sample query")]
+        online: bool,
+
+        #[arg(short, long, requires = "online", help = "Repeat the online check every INTERVAL seconds")]
+        interval: Option<u64>,
+    },
+
+    /// Imports search engines from other programs
+    #[clap(about = "Import search engines from surfraw elvi")]
+    Import {
+        #[arg(long, required = true, help = "Import the installed surfraw elvi")]
+        surfraw: bool,
+
+        #[arg(help = "Directories to scan instead of the surfraw ones")]
+        directories: Vec<PathBuf>,
+    },
+
+    /// Exports a search engine to a format understood by other programs
+    #[clap(about = "Export a search engine as an OpenSearch description or a surfraw elvi")]
+    #[clap(group(ArgGroup::new("format").required(true)))]
+    Export {
+        #[arg(long, value_name = "ENGINE", group = "format", help = "Export the engine as an OpenSearch description document")]
+        opensearch: Option<String>,
+
+        #[arg(long, value_name = "ENGINE", group = "format", help = "Export the engine as a surfraw elvi")]
+        surfraw: Option<String>,
+
+        #[arg(short, long, help = "File to write to, instead of the standard output")]
+        output: Option<PathBuf>,
+    },
+
+    /// Shows the changes made to the configuration
+    #[clap(about = "Show the log of changes made to the configuration")]
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommands,
+    },
+
+    /// Groups the following searches in a session
+    #[clap(about = "Group related searches in sessions and reopen them later")]
+    Session {
+        #[command(subcommand)]
+        command: SessionCommands,
+    },
+
+    /// Searches a topic with every engine of a group, in a session of its own
+    #[clap(about = "Search a topic with every engine of a group, in a session summarized in the notes directory when stopped")]
+    Research {
+        #[arg(help = "Topic to research, also the name of the session")]
+        topic: String,
+
+        #[arg(short, long, default_value = "research", help = "Group of engines to search the topic with")]
+        group: String,
+    },
+
+    /// Opens the landing page of an engine
+    #[clap(about = "Open the landing page of the engine, without searching anything")]
+    Home {
+        #[arg(help = "Search engine, the default one if not given")]
+        name: Option<String>,
+    },
+
+    /// Searches the same term with two engines, side by side
+    #[clap(about = "Search the term with two engines, placing the windows side by side through the browser command")]
+    Compare {
+        #[arg(help = "Engine opened on the left")]
+        left: String,
+
+        #[arg(help = "Engine opened on the right")]
+        right: String,
+
+        #[arg(required = true, help = "Search term")]
+        term: Vec<String>,
+    },
+
+    /// Shows the URL of the term for every engine
+    #[clap(about = "Print the URL every engine generates for the term, without opening anything")]
+    Matrix {
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable, help = "Value of a variable of the url pattern")]
+        variables: Vec<(String, String)>,
+
+        #[arg(long, help = "Search the term as an exact match, in double quotes or the syntax of the engine")]
+        exact: bool,
+
+        #[arg(required = true, help = "Search term")]
+        term: Vec<String>,
+    },
+
+    /// Searches the texts copied to the clipboard
+    #[clap(about = "Search every text copied to the clipboard, after a grace period in which the search can be cancelled")]
+    Watch {
+        #[arg(long, short, help = "Specifies the search engine to be used")]
+        engine: Option<String>,
+    },
+
+    /// Prints the search suggestions of an engine
+    #[clap(about = "Print the search suggestions of the engine for the term, one per line")]
+    Suggest {
+        #[arg(long, short, help = "Specifies the search engine to be used")]
+        engine: Option<String>,
+
+        #[arg(required = true, help = "Search term")]
+        term: Vec<String>,
+    },
+
+    /// Hands a search off to another device on the local network
+    #[clap(about = "Serve the search on the local network and print a QR code pointing at it, to continue it on a phone")]
+    Handoff {
+        #[arg(long, short, help = "Specifies the search engine to be used")]
+        engine: Option<String>,
+
+        #[arg(long, default_value_t = 0, help = "Port to listen on, a free one if 0")]
+        port: u16,
+
+        #[arg(long, value_name = "SECONDS", default_value_t = handoff::DEFAULT_TIMEOUT, help = "Seconds to wait for the search to be opened")]
+        timeout: u64,
+
+        #[arg(required = true, help = "Search term")]
+        term: Vec<String>,
+    },
+
+    /// Shows the searches made
+    #[clap(about = "Show the searches made")]
+    History {
+        #[command(subcommand)]
+        command: HistoryCommands,
+    },
+
+    /// Generates a shell completion script
+    #[clap(about = "Print the completion script for the shell, offering the configured engines and their bang keywords")]
+    Completions {
+        #[arg(value_enum, help = "Shell to generate the script for")]
+        shell: Shell,
+    },
+
+    /// Inspects the configuration file
+    #[clap(about = "Inspect the configuration")]
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
 
+    /// Flags configuration problems, fixing the mechanical ones on demand
+    #[clap(about = "Check the configuration for problems")]
+    Lint {
+        #[arg(long, help = "Fix the problems that can be fixed mechanically")]
+        fix: bool,
+    },
+}
 
-    /// Updates the file path
-    pub fn update_path(&mut self, new: PathBuf) {
-        self.file_path = new;
-    }
 
+/// Enum containing the subcommands that can be executed from the command [Commands::Config]
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Shows a unified diff between the configuration and a reference
+    #[clap(about = "Show what changed compared to the latest backup or to the built-in engines")]
+    Diff {
+        #[arg(long, value_enum, default_value_t = DiffReference::Backup, help = "What to compare the configuration against")]
+        against: DiffReference,
+    },
 
-    /// Removes a search engine based on name
-    pub fn remove_where_name(&mut self, name: &str) -> Result<(), io::Error> {
-        if let Some(content) = &mut self.engines {
-            content.retain(|element| element.name != name);
-            Ok(())
-        } else {
-            info!("Attempting to remove an element from a null vector");
-            Err(io::Error::new(io::ErrorKind::InvalidData, "Attempting to remove an element from a null vector"))
-        }
-    }
+    /// Prints a setting
+    #[clap(about = "Print the value of a setting, by dotted key (e.g. groups.research)")]
+    Get {
+        #[arg(help = "Dotted key of the setting")]
+        key: String,
+    },
 
+    /// Changes a setting
+    #[clap(about = "Change the value of a setting, by dotted key. The value is read as YAML, null unsets it")]
+    Set {
+        #[arg(help = "Dotted key of the setting")]
+        key: String,
 
-    /// Allows an engine to be removed based on UUID
-    pub fn remove_where_uuid(&mut self, uuid: Uuid) -> Result<(), io::Error> {
-        if let Some(content) = &mut self.engines {
-            content.retain(|element| element.uuid != uuid);
-            Ok(())
-        } else {
-            info!("Attempting to remove an element from a null vector");
-            Err(io::Error::new(io::ErrorKind::InvalidData, "Attempting to remove an element from a null vector"))
-        }
-    }
+        #[arg(help = "New value of the setting")]
+        value: String,
+    },
+}
 
 
-    /// Generates a list of the names of the configured search engines
-    pub fn names(&self) -> Vec<String> {
-        match &self.engines {
-            Some(content) => content.iter().map(|element| element.name.clone()).collect(),
-            None => vec![],
-        }
-    }
+/// Enum containing the subcommands that can be executed from the command [Commands::Audit]
+#[derive(Subcommand)]
+enum AuditCommands {
+    /// Prints the recorded changes
+    #[clap(about = "Show the recorded changes, from the oldest to the latest")]
+    Show {
+        #[arg(short, long, help = "Show only the latest LIMIT changes")]
+        limit: Option<usize>,
+    },
+}
 
 
-    /// Returns the default search engine
-    pub fn default(&self) -> Option<Engine> {
-        match &self.default_engine {
-            Some(default) => {
-                self.engines.as_ref()?.iter().find(|&element| element.name == *default).cloned()
-            }
-            None => None
-        }
-    }
+/// Enum containing the subcommands that can be executed from the command [Commands::Session]
+#[derive(Subcommand)]
+enum SessionCommands {
+    /// Starts a session
+    #[clap(about = "Start a session, tagging the following searches with its name")]
+    Start {
+        #[arg(help = "Name of the session")]
+        name: String,
+    },
 
+    /// Stops the active session
+    #[clap(about = "Stop the active session")]
+    Stop,
 
-    /// Sets the default search engine based on name
-    pub fn set_default(&mut self, name: String) -> Result<(), io::Error> {
-        if self.names().contains(&name) {
-            self.default_engine = Some(name);
-            Ok(())
-        } else {
-            Err(io::Error::new(io::ErrorKind::InvalidData, "The search engine passed as an argument is not included in the settings"))
-        }
-    }
+    /// Reopens the searches of a session
+    #[clap(about = "Reopen every search made during the session")]
+    Open {
+        #[arg(help = "Name of the session")]
+        name: String,
+    },
 
+    /// Lists the sessions
+    #[clap(about = "List the sessions recorded in the history")]
+    List,
 
-    /// Returns the search engine based on the name passed as an argument
-    pub fn where_name(&self, name: String) -> Result<Engine, io::Error> {
-        if let Some(engines) = &self.engines {
-            for engine in engines {
-                if engine.name == name {
-                    return Ok(engine.clone());
-                }
-            }
-            Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid engine name"))
-        } else {
-            error!("Attempting to get a search engine from a null configuration file");
-            Err(io::Error::new(io::ErrorKind::Other, "Attempting to get a search engine from a null configuration file"))
-        }
-    }
-}
+    /// Removes sessions from the history
+    #[clap(about = "Remove the searches of a session, or of the sessions older than some days, from the history")]
+    #[clap(group(ArgGroup::new("sessions").required(true)))]
+    Prune {
+        #[arg(group = "sessions", help = "Name of the session")]
+        name: Option<String>,
 
+        #[arg(long, value_name = "DAYS", group = "sessions", help = "Prune every session whose latest search is older than DAYS days")]
+        older_than: Option<u64>,
+    },
 
-/// Class responsible for intermediate the command line with the executable.
-/// [Parser], belonging to *Clap*, is used to generate the implementation for the command line.
-/// command macros are used to add information to the command line, according to their name.
-#[derive(Parser)]
-#[command(author = "Arthur Valadares Campideli", version, about = "An application to open a search term from the command line", long_about = "This application was created with the aim of adding a shortcut to the keyboard in order to search the selected text", subcommand_negates_reqs = true)]
-#[command(propagate_version = true)]
-struct Cli {
-    /// Optional argument. If none is specified, the default will be used
-    #[arg(long, short, help = "Specifies the search engine to be used")]
-    engine: Option<String>,
+    /// Exports the searches of a session as a list of links
+    #[clap(about = "Export the searches of a session as a Markdown or HTML list of links")]
+    Export {
+        #[arg(help = "Name of the session")]
+        name: String,
 
-    /// Commands that can be executed
-    #[command(subcommand)]
-    commands: Option<Commands>,
+        #[arg(short, long, value_enum, default_value_t = SessionFormat::Markdown, help = "Format of the list")]
+        format: SessionFormat,
 
-    /// The search term to be used, possibly null, in this case the selected text will be used
-    #[arg(num_args(0..), help = "Specify the term to be searched for")]
-    term: Option<Vec<String>>,
+        #[arg(short, long, help = "File to write to, instead of the standard output")]
+        output: Option<PathBuf>,
+    },
 }
 
 
-/// Enum containing the subcommands that can be executed from the Cli.
-#[derive(Subcommand)]
-enum Commands {
-    /// Lists the configured search engines
-    #[clap(about = "List configured search engines")]
-    List,
+/// Formats a session can be exported to by [SessionCommands::Export]
+#[derive(ValueEnum, Clone, Copy)]
+enum SessionFormat {
+    /// Markdown list of links
+    Markdown,
 
-    /// Defines and shows the default search engine configured
-    #[clap(about = "Show the default search engine")]
-    Default,
+    /// HTML page with a list of links
+    Html,
+}
 
-    #[clap(about = "Set the default search engine")]
-    SetDefault { name: String },
 
-    /// Adds a search engine based on the values requested by [Engine::new]
-    #[clap(about = "Add a search engine")]
-    Add {
-        #[arg(help = "Search engine name")]
-        name: Option<String>,
+/// Enum containing the subcommands that can be executed from the command [Commands::History]
+#[derive(Subcommand)]
+enum HistoryCommands {
+    /// Prints the searches made
+    #[clap(about = "List the searches made, from the oldest to the latest")]
+    List {
+        #[arg(short, long, help = "Show only the latest LIMIT searches")]
+        limit: Option<usize>,
 
-        #[arg(help = "Search engine url pattern")]
-        url_pattern: Option<String>,
+        #[arg(long, default_value_t = 0, help = "Skip the latest OFFSET searches, to page back in time with --limit")]
+        offset: usize,
 
-        #[arg(help = "Pattern that will be replaced by the treated search term")]
-        pattern: Option<String>,
+        #[arg(short, long, help = "Show only the searches made during the session")]
+        session: Option<String>,
 
-        #[arg(help = "Regex that will be applied to the search term")]
-        regex: Option<String>,
+        #[arg(long, help = "Show the searches through the pager, $PAGER or less")]
+        pager: bool,
+    },
+}
 
-        #[arg(help = "Value by which the regex will be replaced")]
-        replacement: Option<String>,
 
-        #[arg(short, long, help = "Force the addition of a new search engine with a repeated name")]
-        force: bool,
+/// Enum containing the subcommands that can be executed from the command [Commands::Archive]
+#[derive(Subcommand)]
+enum ArchiveCommands {
+    /// Prints the archived engines
+    #[clap(about = "List the removed search engines")]
+    List,
 
-        #[arg(short, long, help = "Adds a new search engine interactively")]
-        interactive: bool,
+    /// Moves an archived engine back to the configured ones
+    #[clap(about = "Restore a removed search engine")]
+    Restore {
+        #[arg(help = "Name of the removed search engine")]
+        name: String,
     },
+}
 
-    /// Removes a search engine based on name
-    #[clap(about = "Remove a search engine based on name or uuid")]
-    Remove {
-        value: String,
 
-        #[arg(short, long)]
-        uuid: bool,
+/// Enum containing the subcommands that can be executed from the command [Commands::Favicons]
+#[derive(Subcommand)]
+enum FaviconsCommands {
+    /// Downloads the favicons of the engines into the cache
+    #[clap(about = "Download the favicons of the search engines not downloaded yet")]
+    Fetch {
+        #[arg(long, help = "Download the favicons of every engine again")]
+        refresh: bool,
     },
+}
 
-    #[clap(about = "Shows a specific search engine or all")]
-    Show {
-        name: Option<String>,
 
-        #[arg(short, long, required_unless_present = "name")]
-        all: bool,
-    },
+/// References the configuration can be compared against by [ConfigCommands::Diff]
+#[derive(ValueEnum, Clone, Copy)]
+enum DiffReference {
+    /// The latest backup, taken before the last time the configuration was saved
+    Backup,
 
-    #[clap(about = "Open the file containing the settings")]
-    Open {
-        #[arg(short, long, help = "Open the file in the system's default terminal editor")]
-        terminal: bool
-    },
+    /// The built-in engines installed by `init`
+    Defaults,
 }
 
 
@@ -466,83 +2388,211 @@ fn main() {
         let search_config_path = search_dir.join("search_config.yaml");
 
         let cli = Cli::parse();
+        if cli.plain || cli.json {
+            progress::disable();
+        }
 
         match Configuration::from(search_config_path.clone()) {
             Ok(mut config) => {
+                if config.tampered {
+                    warn_tampered(&config, &search_dir.join("backups"), cli.json);
+                }
 
                 if let Some(command) = cli.commands {
+                    let snapshot = audit::Snapshot::of(&config);
+                    let arguments = std::env::args().skip(1).collect::<Vec<String>>().join(" ");
+
                     match command {
-                        Commands::Add { name, url_pattern, pattern, regex, replacement, force, interactive } => {
-                            if interactive {
-                                let engine = Engine::prompt_from_user();
-                                config.push(engine);
+                        Commands::Add { defaults: true, .. } => {
+                            print_added(&config.merge(presets::defaults()), cli.json);
+                        }
+                        Commands::Add { preset: Some(preset), .. } => {
+                            print_added(&config.merge(presets::preset(&preset).unwrap_or_default()), cli.json);
+                        }
+                        Commands::Add { name, url_pattern, pattern, regex, replacement, force, interactive, dry_run, sample, extras, .. } => {
+                            let engine = if interactive {
+                                Some(Engine::prompt_from_user())
                             } else {
                                 let name = name.unwrap();
-                                if force || !config.names().contains(&name.clone()) {
-                                    config.push(Engine::new(
+                                if force || !config.contains(&name) {
+                                    Some(Engine::new(
                                         name.as_str(),
                                         url_pattern.unwrap().as_str(),
                                         pattern.unwrap().as_str(),
                                         regex.unwrap().as_str(),
                                         replacement.unwrap().as_str(),
-                                    ));
+                                    ))
                                 } else {
-                                    eprintln!("The config file already contains a search engine named {}", name);
+                                    let e = io::Error::new(io::ErrorKind::AlreadyExists, format!("The config file already contains a search engine named {}", name));
+                                    print_error(&e, cli.json);
+                                    None
+                                }
+                            };
+
+                            if let Some(mut engine) = engine {
+                                extras.apply(&mut engine);
+                                let name = engine.name.clone();
+                                if dry_run && cli.json {
+                                    let url = engine.url(&engine.narrow(&sample)).ok();
+                                    print_json(&json!({"engine": engine, "url": url}));
+                                } else if dry_run {
+                                    preview_engine(engine, sample.as_str());
+                                } else {
+                                    let added = if force {
+                                        config.replace(engine);
+                                        Ok(true)
+                                    } else {
+                                        add_engine(&mut config, engine, !cli.json)
+                                    };
+                                    match added {
+                                        Ok(true) if cli.json => print_added(&[name], true),
+                                        Ok(_) => {}
+                                        Err(e) => {
+                                            error!("Unable to add the search engine. Error: {}", e);
+                                            print_error(&e, cli.json);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        Commands::Edit { name, extras } => {
+                            if extras.is_empty() {
+                                eprintln!("Nothing to change, see `search edit --help`");
+                            } else if let Err(e) = config.edit(name, extras) {
+                                error!("Unable to edit the search engine. Error: {}", e);
+                                print_error(&e, cli.json);
+                            }
+                        }
+                        Commands::Tutorial => tutorial::run(&mut config),
+                        Commands::Init { locale, no_locale } => {
+                            let engines = match init_locale(locale, no_locale, cli.json) {
+                                Some(locale) => presets::localize(presets::defaults(), &locale),
+                                None => presets::defaults(),
+                            };
+                            print_added(&config.merge(engines), cli.json);
+                            if config.default().is_none() {
+                                match config.set_default(presets::DEFAULT_ENGINE.to_string()) {
+                                    Ok(_) => println!("Default search engine: {}", presets::DEFAULT_ENGINE),
+                                    Err(e) => error!("Unable to set the default search engine. Error: {}", e),
                                 }
                             }
                         }
-                        Commands::Remove { value, uuid } => {
-                            if uuid {
-                                if let Ok(uuid) = Uuid::from_str(value.as_str()) {
-                                    match config.remove_where_uuid(uuid) {
-                                        Ok(_) => info!("Successful removal of {} engine", value),
-                                        Err(_) => error!("Failed to remove {} from the search engines list", value),
+                        Commands::Reset { yes } => {
+                            let confirmed = yes || Confirm::new("Replace every configured engine by the built-in ones? The settings, groups and archive are kept, and a backup is written first.")
+                                .with_default(false)
+                                .prompt()
+                                .unwrap_or(false);
+
+                            if confirmed {
+                                match config.backup(&search_dir.join("backups")) {
+                                    Ok(path) => {
+                                        println!("Previous configuration saved to {}", path.display());
+                                        config.reset_engines(presets::defaults());
+                                        if let Err(e) = config.set_default(presets::DEFAULT_ENGINE.to_string()) {
+                                            error!("Unable to set the default search engine. Error: {}", e);
+                                        }
+                                    }
+                                    Err(e) => {
+                                        error!("Unable to back up the configuration. Error: {}", e);
+                                        eprintln!("Unable to back up the configuration, nothing was reset.");
                                     }
-                                } else {
-                                    error!("Unable to convert {} to a uuid.", value);
                                 }
+                            }
+                        }
+                        Commands::Remove { value, uuid, purge } => {
+                            let removed = if uuid {
+                                config.remove_where_uuid(value.as_str(), purge)
                             } else {
-                                match config.remove_where_name(value.as_str()) {
-                                    Ok(_) => info!("Successful removal of {} engine", value),
-                                    Err(_) => error!("Failed to remove {} from the search engines list", value),
+                                config.remove_where_name(value.as_str(), purge)
+                            };
+                            match removed {
+                                Ok(name) => {
+                                    info!("Successful removal of {} engine", name);
+                                    if cli.json {
+                                        print_json(&json!({"removed": name, "archived": !purge}));
+                                    }
+                                }
+                                Err(e) => {
+                                    error!("Failed to remove {} from the search engines list", value);
+                                    print_error(&e, cli.json);
                                 }
                             }
                         }
-                        Commands::List => {
-                            for name in config.names() {
-                                println!("- {}", name);
+                        Commands::List { long, limit, offset, pager } => {
+                            let engines = config.engines.iter().skip(offset).take(limit.unwrap_or(usize::MAX));
+                            if cli.json {
+                                let default = config.default().map(|engine| engine.uuid);
+                                let engines: Vec<_> = engines
+                                    .map(|engine| json!({
+                                        "uuid": engine.uuid,
+                                        "name": engine.name,
+                                        "url_pattern": engine.url_pattern,
+                                        "aliases": engine.aliases,
+                                        "default": Some(engine.uuid) == default,
+                                    }))
+                                    .collect();
+                                print_json(&engines);
+                            } else {
+                                let directory = favicons::directory(&search_dir);
+                                let protocol = favicons::Protocol::detect(pager);
+                                let engines: Vec<&Engine> = engines.collect();
+                                let marker = |engine: &Engine| if config.settings.favicons {
+                                    favicons::icon(&directory, engine, protocol)
+                                } else {
+                                    String::from("-")
+                                };
+
+                                let hyperlink = config.settings.hyperlinks(cli.hyperlink);
+                                let lines: Vec<String> = engines.iter()
+                                    .map(|engine| if long {
+                                        let url_pattern = link(&engine.url_pattern, &engine.home(), hyperlink);
+                                        format!("{} {}  {:<20} {}", marker(engine), engine.short_uuid(), engine.name, url_pattern)
+                                    } else {
+                                        format!("{} {}", marker(engine), engine.name)
+                                    })
+                                    .collect();
+                                print_lines(&lines, pager);
+
+                                if config.settings.favicons && !favicons::missing(&directory, engines.into_iter()).is_empty() {
+                                    favicons::fetch_in_background(cli.tor);
+                                }
                             }
                         }
                         Commands::Default => {
-                            if let Some(default_engine) = config.default() {
+                            if cli.json {
+                                print_json(&config.default());
+                            } else if let Some(default_engine) = config.default() {
                                 println!("- {}", default_engine.name)
                             } else {
-                                eprintln!("No default engine defined!")
+                                print_error(&io::Error::new(io::ErrorKind::NotFound, "No default engine defined!"), cli.json);
                             }
                         }
                         Commands::SetDefault { name } => {
-                            if config.names().contains(&name) {
-                                match config.set_default(name.clone()) {
-                                    Ok(_) => { info!("Updated default search engine") }
-                                    Err(e) => {
-                                        error!("Unable to update default search engine. Error: {}", e);
-                                        eprintln!("Unable to update default search engine.");
-                                    }
+                            match config.set_default(name.clone()) {
+                                Ok(_) => { info!("Updated default search engine") }
+                                Err(e) => {
+                                    error!("Unable to update default search engine. Error: {}", e);
+                                    print_error(&e, cli.json);
                                 }
-                            } else {
-                                eprintln!("Config file does not contains {} search engine.", name);
                             }
                         }
-                        Commands::Show { name, all } => {
-                            if let Some(engines) = config.engines.clone() {
+                        Commands::Show { name, all, uuid } => {
+                            if cli.json && all {
+                                print_json(&config.engines);
+                            } else if !config.engines.is_empty() {
                                 if all {
-                                    for engine in engines {
+                                    for engine in config.engines.iter() {
                                         print_engine_as_yaml(engine);
                                     }
                                 } else if let Some(value) = name {
-                                    match config.where_name(value.clone()) {
-                                        Ok(engine) => print_engine_as_yaml(engine),
-                                        Err(_) => warn!("There is no engine defined named {}", value),
+                                    let engine = if uuid { config.where_uuid(&value) } else { config.where_name(value.clone()) };
+                                    match engine {
+                                        Ok(engine) if cli.json => print_json(&engine),
+                                        Ok(engine) => print_engine_as_yaml(&engine),
+                                        Err(e) => {
+                                            warn!("There is no engine defined named {}", value);
+                                            print_error(&e, cli.json);
+                                        }
                                     }
                                 }
                             } else {
@@ -551,32 +2601,304 @@ fn main() {
                         }
                         Commands::Open { terminal } => {
                             open_file(search_config_path.clone(), terminal, "Configuration file");
+                            if terminal {
+                                match Configuration::from(search_config_path.clone()) {
+                                    Ok(edited) => {
+                                        config = edited;
+                                        if let Err(e) = config.store_checksum() {
+                                            error!("Unable to store the configuration checksum. Error: {}", e);
+                                        }
+                                    }
+                                    Err(e) => error!("Unable to load the edited configuration. Error: {}", e),
+                                }
+                            }
+                        }
+                        Commands::Audit { command: AuditCommands::Show { limit } } => {
+                            audit::show(&search_dir.join("audit.log"), limit, cli.json);
+                        }
+                        Commands::Session { command } => {
+                            let mut state = State::load(search_dir.join("state.yaml"));
+                            match command {
+                                SessionCommands::Start { name } => {
+                                    if let Some(previous) = session::start(&mut state, &name) {
+                                        research::conclude(&config, &search_dir.join("history.jsonl"), &mut state, &previous);
+                                    }
+                                }
+                                SessionCommands::Stop => {
+                                    if let Some(stopped) = session::stop(&mut state) {
+                                        research::conclude(&config, &search_dir.join("history.jsonl"), &mut state, &stopped);
+                                    }
+                                }
+                                SessionCommands::Open { name } => {
+                                    let options = SearchOptions {
+                                        terminal_browser: cli.terminal_browser,
+                                        hyperlink: config.settings.hyperlinks(cli.hyperlink),
+                                        ..SearchOptions::from_config(&config, &search_dir)
+                                    };
+                                    session::open(&config, &search_dir.join("history.jsonl"), &name, &options, &mut state);
+                                }
+                                SessionCommands::List => session::list(&search_dir.join("history.jsonl"), &state, cli.json),
+                                SessionCommands::Prune { name, older_than } => {
+                                    session::prune(&search_dir.join("history.jsonl"), name.as_deref(), older_than);
+                                }
+                                SessionCommands::Export { name, format, output } => {
+                                    match session::export(&search_dir.join("history.jsonl"), &name, format) {
+                                        Some(content) => write_output(&content, output),
+                                        None => eprintln!("No searches recorded in the session {}", name),
+                                    }
+                                }
+                            }
+                            if let Err(e) = state.save() {
+                                error!("Unable to save the state file. Error: {}", e);
+                            }
+                        }
+                        Commands::Research { topic, group } => {
+                            let options = SearchOptions {
+                                terminal_browser: cli.terminal_browser,
+                                tor: cli.tor,
+                                ..SearchOptions::from_config(&config, &search_dir)
+                            };
+                            let mut state = State::load(search_dir.join("state.yaml"));
+                            research::start(&config, &options, &mut state, &macros::expand(&topic), &group);
+                            if let Err(e) = state.save() {
+                                error!("Unable to save the state file. Error: {}", e);
+                            }
+                        }
+                        Commands::Home { name } => {
+                            let engine = match name {
+                                Some(name) => config.where_name(name),
+                                None => config.default().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "There is no defined default search engine")),
+                            };
+                            match engine {
+                                Ok(engine) => {
+                                    let mut state = State::load(search_dir.join("state.yaml"));
+                                    let options = SearchOptions {
+                                        terminal_browser: cli.terminal_browser,
+                                        ..SearchOptions::from_config(&config, &search_dir)
+                                    };
+                                    open_url(&engine, &engine.home(), &options, &mut state);
+                                    if let Err(e) = state.save() {
+                                        error!("Unable to save the state file. Error: {}", e);
+                                    }
+                                }
+                                Err(e) => print_error(&e, cli.json),
+                            }
+                        }
+                        Commands::Watch { engine } => {
+                            let named = engine.clone().filter(|name| !meta::NAMES.contains(&name.as_str()));
+                            if let Some(Err(e)) = named.map(|name| config.where_name(name)) {
+                                print_error(&e, cli.json);
+                                std::process::exit(1);
+                            }
+                            watch::run(&mut config, &search_dir, engine);
+                        }
+                        Commands::Suggest { engine, term } => {
+                            let engine = match engine {
+                                Some(name) => config.where_name(name),
+                                None => config.default().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "There is no defined default search engine")),
+                            };
+                            let cache = Cache::new(search_dir.join("cache"), Duration::from_secs(config.settings.http.cache_ttl_secs));
+                            match engine.and_then(|engine| suggest::fetch(&engine, &term.join(" "), &config.settings.http.for_engine(&engine, cli.tor), &cache)) {
+                                Ok(suggestions) if cli.json => print_json(&suggestions),
+                                Ok(suggestions) => suggestions.iter().for_each(|suggestion| println!("{}", suggestion)),
+                                Err(e) => {
+                                    error!("Unable to fetch the suggestions. Error: {}", e);
+                                    print_error(&e, cli.json);
+                                }
+                            }
+                        }
+                        Commands::Handoff { engine, port, timeout, term } => {
+                            let engine = match engine {
+                                Some(name) => config.where_name(name),
+                                None => config.default().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "There is no defined default search engine")),
+                            };
+                            let url = engine.and_then(|engine| {
+                                bounded_url(&engine, &macros::expand(&term.join(" ")), &SearchOptions::from_config(&config, &search_dir))
+                            });
+                            let served = url.and_then(|url| handoff::serve(&url, port, Duration::from_secs(timeout)));
+                            if let Err(e) = served {
+                                error!("Unable to hand the search off. Error: {}", e);
+                                print_error(&e, cli.json);
+                            }
+                        }
+                        Commands::Matrix { variables, exact, term } => {
+                            let options = SearchOptions {
+                                variables: variables.into_iter().collect(),
+                                hyperlink: config.settings.hyperlinks(cli.hyperlink),
+                                exact,
+                                ..SearchOptions::from_config(&config, &search_dir)
+                            };
+                            print_matrix(&config, &macros::expand(&term.join(" ")), &options, cli.json);
+                        }
+                        Commands::Compare { left, right, term } => match (config.where_name(left), config.where_name(right)) {
+                            (Ok(left), Ok(right)) => {
+                                let mut state = State::load(search_dir.join("state.yaml"));
+                                let term = macros::expand(&term.join(" "));
+                                for (engine, side) in [(left, "left"), (right, "right")] {
+                                    let options = SearchOptions {
+                                        terminal_browser: cli.terminal_browser,
+                                        side: Some(side),
+                                        tor: cli.tor,
+                                        ..SearchOptions::from_config(&config, &search_dir)
+                                    };
+                                    search(&engine, &term, &options, &mut state);
+                                    state.opened(&engine);
+                                }
+                                if let Err(e) = state.save() {
+                                    error!("Unable to save the state file. Error: {}", e);
+                                }
+                            }
+                            (left, right) => {
+                                for e in [left.err(), right.err()].into_iter().flatten() {
+                                    print_error(&e, cli.json);
+                                }
+                            }
+                        },
+                        Commands::Apply { source } => match apply::run(&mut config, &source) {
+                            Ok(changes) if cli.json => print_json(&json!({"changes": changes})),
+                            Ok(changes) => changes.iter().for_each(|change| println!("{}", change)),
+                            Err(e) => {
+                                error!("Unable to apply the changes. Error: {}", e);
+                                print_error(&e, cli.json);
+                                std::process::exit(1);
+                            }
+                        },
+                        Commands::Favicons { command: FaviconsCommands::Fetch { refresh } } => {
+                            favicons::fetch_all(&config, &favicons::directory(&search_dir), refresh, cli.tor);
+                        }
+                        Commands::Archive { command: ArchiveCommands::List } => {
+                            if cli.json {
+                                print_json(&config.archived);
+                            } else {
+                                for engine in &config.archived {
+                                    println!("- {}", engine.name);
+                                }
+                            }
+                        }
+                        Commands::Archive { command: ArchiveCommands::Restore { name } } => {
+                            if let Err(e) = config.restore(&name) {
+                                error!("Unable to restore {}. Error: {}", name, e);
+                                print_error(&e, cli.json);
+                            }
+                        }
+                        Commands::History { command: HistoryCommands::List { limit, offset, session, pager } } => {
+                            let hyperlink = config.settings.hyperlinks(cli.hyperlink);
+                            let page = history::Page { limit, offset, pager };
+                            history::list(&search_dir.join("history.jsonl"), session.as_deref(), page, hyperlink, cli.json);
+                        }
+                        Commands::Query(args) => query_or_exit(&config, &search_dir, QueryArgs {
+                            term: expand_macros(args.term),
+                            hyperlink: cli.hyperlink,
+                            tor: cli.tor,
+                            json: cli.json,
+                            ..args
+                        }),
+                        Commands::Completions { shell } => print_completions(&config, shell),
+                        Commands::Doctor { online, interval } => {
+                            doctor::run(&mut config, search_dir.join("health.yaml"), online, interval, cli.tor, cli.json);
+                        }
+                        Commands::Lint { fix } => lint::run(&mut config, fix),
+                        Commands::Config { command: ConfigCommands::Diff { against } } => {
+                            print_diff(&config, against, &search_dir.join("backups"));
+                        }
+                        Commands::Config { command: ConfigCommands::Get { key } } => match keys::get(&config, &key) {
+                            Ok(value) => keys::print(&value),
+                            Err(e) => {
+                                print_error(&e, cli.json);
+                                std::process::exit(1);
+                            }
+                        },
+                        Commands::Config { command: ConfigCommands::Set { key, value } } => {
+                            if let Err(e) = keys::set(&mut config, &key, &value) {
+                                error!("Unable to set {}. Error: {}", key, e);
+                                print_error(&e, cli.json);
+                                std::process::exit(1);
+                            }
+                        }
+                        Commands::Import { directories, .. } => {
+                            let directories = if directories.is_empty() { surfraw::default_directories() } else { directories };
+                            let spinner = progress::spinner("Scanning the surfraw elvi");
+                            let engines = surfraw::scan(&directories);
+                            spinner.finish_and_clear();
+                            let (mut added, mut merged, mut skipped) = (vec![], vec![], vec![]);
+                            for engine in engines {
+                                let name = engine.name.clone();
+                                if config.contains(&name) {
+                                    skipped.push(name);
+                                } else {
+                                    match add_engine(&mut config, engine, false) {
+                                        Ok(true) => added.push(name),
+                                        Ok(false) => merged.push(name),
+                                        Err(e) => {
+                                            warn!("Unable to import {}. Error: {}", name, e);
+                                            skipped.push(name);
+                                        }
+                                    }
+                                }
+                            }
+                            if cli.json {
+                                print_json(&json!({"added": added, "merged": merged, "skipped": skipped}));
+                            } else {
+                                skipped.iter().for_each(|name| println!("= {} (already configured)", name));
+                                merged.iter().for_each(|name| println!("~ {} (alias of an identical engine)", name));
+                                added.iter().for_each(|name| println!("+ {}", name));
+                            }
+                        }
+                        Commands::Export { opensearch, surfraw, output } => {
+                            let (name, format): (String, fn(&Engine) -> String) = match (opensearch, surfraw) {
+                                (Some(name), _) => (name, opensearch::descriptor),
+                                (_, Some(name)) => (name, surfraw::elvi),
+                                (None, None) => unreachable!("clap requires one of the formats"),
+                            };
+                            match config.where_name(name) {
+                                Ok(engine) => write_output(format(&engine).as_str(), output),
+                                Err(e) => print_error(&e, cli.json),
+                            }
                         }
                     }
 
-                    if let Err(e) = config.save() {
-                        error!("Failed to save file. Error: {}", e);
+                    let persisted = if !config.modified {
+                        info!("The configuration was not changed, skipping save");
+                        true
+                    } else if cli.no_save {
+                        info!("The configuration was changed but --no-save was passed, skipping save");
+                        false
                     } else {
-                        info!("The file has been saved successfully");
+                        if let Err(e) = config.backup(&search_dir.join("backups")) {
+                            warn!("Unable to back up the configuration, saving it anyway. Error: {}", e);
+                            let e = io::Error::new(e.kind(), format!("Unable to back up the configuration, saving it anyway: {}", e));
+                            print_error(&e, cli.json);
+                        }
+                        match config.save() {
+                            Ok(_) => {
+                                info!("The file has been saved successfully");
+                                true
+                            }
+                            Err(e) => {
+                                error!("Failed to save file. Error: {}", e);
+                                print_error(&e, cli.json);
+                                false
+                            }
+                        }
+                    };
+
+                    if persisted {
+                        audit::record(&search_dir.join("audit.log"), &arguments, &snapshot, &config);
                     }
                 } else {
-                    let engine = cli.engine.map_or_else(|| config.default().unwrap_or_else(|| {
-                        error!("There is no defined default search engine.");
-                        std::process::exit(1);
-                    }), |engine_name| {
-                        config.where_name(engine_name).unwrap_or_else(|_| {
-                            error!("Engine not found. Using default search engine.");
-                            config.default().expect("No search engine specified.")
-                        })
+                    query_or_exit(&config, &search_dir, QueryArgs {
+                        engine: cli.engine,
+                        variables: cli.doc_version.into_iter().map(|version| (String::from("version"), version)).collect(),
+                        terminal_browser: cli.terminal_browser,
+                        join: cli.join,
+                        separate: cli.separate,
+                        exact: cli.exact,
+                        hyperlink: cli.hyperlink,
+                        tor: cli.tor,
+                        json: cli.json,
+                        term: expand_macros(cli.term),
+                        ..QueryArgs::default()
                     });
-
-                    if let Some(queries) = cli.term {
-                        for query in queries {
-                            open_browser(&engine, query.as_str());
-                        }
-                    } else {
-                        open_browser(&engine, get_text().as_str());
-                    }
                 }
             }
             Err(_) => {
@@ -585,3 +2907,131 @@ fn main() {
         }
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use clap::error::ErrorKind;
+
+    use super::*;
+
+
+    #[test]
+    fn add_defaults_and_preset_accept_global_flags_after_them() {
+        for args in [
+            vec!["search", "add", "--defaults", "--json"],
+            vec!["search", "add", "--defaults", "--no-save"],
+            vec!["search", "add", "--preset", "developer", "--json"],
+        ] {
+            assert!(Cli::try_parse_from(args).is_ok());
+        }
+    }
+
+
+    #[test]
+    fn reset_engines_keeps_the_other_sections() {
+        let custom = Engine::new("custom", "https://custom.test/?q={{term}}", "{{term}}", r"\s+", "+");
+        let mut config = Configuration::new(PathBuf::from("/nonexistent/search_config.yaml"), Some("custom".to_string()), vec![custom]);
+        config.settings.history = false;
+
+        config.reset_engines(presets::defaults());
+        assert!(!config.contains("custom"));
+        assert!(config.contains(presets::DEFAULT_ENGINE));
+        assert!(config.default_engine.is_none());
+        assert!(!config.settings.history);
+    }
+
+
+    #[test]
+    fn push_refuses_configured_names_and_replace_overwrites_them() {
+        let engine = |url: &str| Engine::new("custom", url, "{{term}}", r"\s+", "+");
+        let mut config = Configuration::new(PathBuf::from("/nonexistent/search_config.yaml"), None, vec![engine("https://a.test/?q={{term}}")]);
+
+        let error = config.push(engine("https://b.test/?q={{term}}")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(config.engines.named("custom").unwrap().url_pattern, "https://a.test/?q={{term}}");
+
+        config.replace(engine("https://b.test/?q={{term}}"));
+        assert_eq!(config.engines.named("custom").unwrap().url_pattern, "https://b.test/?q={{term}}");
+    }
+
+
+    #[test]
+    fn query_fails_instead_of_exiting_when_the_engine_is_gone() {
+        let config = Configuration::new(PathBuf::from("/nonexistent/search_config.yaml"), None, vec![]);
+        let args = QueryArgs { engine: Some("removed".to_string()), term: Some(vec!["term".to_string()]), ..QueryArgs::default() };
+        assert_eq!(query(&config, Path::new("/nonexistent"), args).unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+
+    #[test]
+    fn bounded_url_truncates_or_aborts_long_terms() {
+        let engine = Engine::new("example", "https://example.test/?q={{term}}", "{{term}}", r"\s+", "+");
+        let mut options = SearchOptions::default();
+        options.settings.max_url_length = 37;
+
+        assert_eq!(bounded_url(&engine, "one two three four", &options).unwrap(), "https://example.test/?q=one+two+three");
+        assert_eq!(bounded_url(&engine, "averyveryverylongword", &options).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+        options.settings.long_urls = LongUrls::Abort;
+        assert_eq!(bounded_url(&engine, "one two three four", &options).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+        options.settings.max_url_length = 0;
+        assert_eq!(bounded_url(&engine, "one two three four", &options).unwrap(), "https://example.test/?q=one+two+three+four");
+    }
+
+
+    #[test]
+    fn bounded_url_keeps_the_suffix_and_quotes_of_truncated_terms() {
+        let mut engine = Engine::new("example", "https://example.test/?q={{term}}", "{{term}}", r"\s+", "+");
+        engine.query_suffix = Some("lang:en".to_string());
+        let mut options = SearchOptions { exact: true, ..SearchOptions::default() };
+        options.settings.max_url_length = 45;
+
+        let url = bounded_url(&engine, "one two three four", &options).unwrap();
+        assert_eq!(url, "https://example.test/?q=\"one+two\"+lang:en");
+    }
+
+
+    #[test]
+    fn where_name_resolves_unique_prefixes() {
+        let engine = |name: &str| Engine::new(name, "https://example.test/?q={{term}}", "{{term}}", r"\s+", "+");
+        let mut duckduckgo = engine("duckduckgo");
+        duckduckgo.aliases = vec!["ddg".to_string()];
+        let config = Configuration::new(
+            PathBuf::from("/nonexistent/search_config.yaml"),
+            None,
+            vec![duckduckgo, engine("github"), engine("gitlab"), engine("git")],
+        );
+
+        assert_eq!(config.where_name("duck".to_string()).unwrap().name, "duckduckgo");
+        assert_eq!(config.where_name("DD".to_string()).unwrap().name, "duckduckgo");
+        assert_eq!(config.where_name("git".to_string()).unwrap().name, "git");
+        assert_eq!(config.where_name("gi".to_string()).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(config.where_name("bing".to_string()).unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+
+    #[test]
+    fn add_requires_every_engine_argument_unless_interactive() {
+        for args in [vec!["search", "add", "foo"], vec!["search", "add", "foo", "https://foo.test/?q={{term}}", "{{term}}", r"\s+"]] {
+            let error = Cli::try_parse_from(args).err().unwrap();
+            assert_eq!(error.kind(), ErrorKind::MissingRequiredArgument);
+        }
+        assert!(Cli::try_parse_from(["search", "add", "-i"]).is_ok());
+        assert!(Cli::try_parse_from(["search", "add", "foo", "https://foo.test/?q={{term}}", "{{term}}", r"\s+", "+"]).is_ok());
+    }
+
+
+    #[test]
+    fn add_defaults_conflicts_with_engine_arguments() {
+        for args in [
+            vec!["search", "add", "--defaults", "name"],
+            vec!["search", "add", "--defaults", "--preset", "developer"],
+            vec!["search", "add", "--preset", "developer", "--force"],
+        ] {
+            let error = Cli::try_parse_from(args).err().unwrap();
+            assert_eq!(error.kind(), ErrorKind::ArgumentConflict);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::io;
+
+use log::info;
+use rand::distributions::{Distribution, WeightedIndex};
+
+use crate::{normalize_name, Configuration, Engine};
+use crate::state::State;
+
+/// Pseudo-engine picking a random web engine for each search, weighted by their `weight`
+pub const RANDOM: &str = "@random";
+
+/// Pseudo-engine cycling through the engines of the `rotation`, one search after the other
+pub const ROTATE: &str = "@rotate";
+
+/// Names of the pseudo-engines, accepted wherever an engine is given on the command line
+pub const NAMES: [&str; 2] = [RANDOM, ROTATE];
+
+
+/// Picks a random web engine, each engine being as likely as its `weight`, 1 if not set. Engines weighing 0 are
+/// never picked.
+fn random(config: &Configuration) -> Result<Engine, io::Error> {
+    let engines: Vec<&Engine> = config.engines.iter().filter(|engine| engine.kind.is_web()).collect();
+    let weights = engines.iter().map(|engine| engine.weight.unwrap_or(1));
+
+    let index = WeightedIndex::new(weights)
+        .map_err(|_| io::Error::new(io::ErrorKind::NotFound, "No web engine to pick from, or every one weighs 0"))?;
+    let engine = engines[index.sample(&mut rand::thread_rng())].clone();
+
+    info!("{} picked {}", RANDOM, engine.name);
+    Ok(engine)
+}
+
+
+/// Picks the next engine of the rotation, counting the searches made with it in the state so that the queries are
+/// distributed evenly across invocations
+fn rotate(config: &Configuration, state: &mut State) -> Result<Engine, io::Error> {
+    if config.rotation.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "No engines configured in the rotation"));
+    }
+
+    let name = &config.rotation[state.rotation % config.rotation.len()];
+    let engine = config.where_name(name.clone())?;
+    state.rotation = state.rotation.wrapping_add(1);
+
+    info!("{} picked {}", ROTATE, engine.name);
+    Ok(engine)
+}
+
+
+/// Resolves the name given on the command line, which can be the name of a pseudo-engine or any name accepted by
+/// [Configuration::where_name]
+pub fn resolve(config: &Configuration, state: &mut State, name: String) -> Result<Engine, io::Error> {
+    match normalize_name(&name).as_str() {
+        RANDOM => random(config),
+        ROTATE => rotate(config, state),
+        _ => config.where_name(name),
+    }
+}
@@ -0,0 +1,56 @@
+use crate::Engine;
+
+/// Placeholder used by OpenSearch url templates for the search term
+const SEARCH_TERMS: &str = "{searchTerms}";
+
+/// Maximum length of the `ShortName` element, according to the OpenSearch specification
+const SHORT_NAME_LENGTH: usize = 16;
+
+
+/// Escapes the characters that can't appear literally in XML text or attribute values
+pub fn escape(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+
+/// Generates an OpenSearch description document for the engine, so that it can be registered in the browser.
+/// The pattern is replaced by `{searchTerms}`, which the browser fills with the url-encoded term, so the regex and
+/// replacement of the engine are not part of the descriptor.
+pub fn descriptor(engine: &Engine) -> String {
+    let template = engine.url_pattern.replace(&engine.pattern, SEARCH_TERMS);
+    let short_name: String = engine.name.chars().take(SHORT_NAME_LENGTH).collect();
+
+    format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>{}</ShortName>
+  <Description>{} search</Description>
+  <InputEncoding>UTF-8</InputEncoding>
+  <Url type="text/html" method="get" template="{}"/>
+</OpenSearchDescription>
+"#, escape(&short_name), escape(&engine.name), escape(&template))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn escape_replaces_the_xml_special_characters() {
+        assert_eq!(escape(r#"<a href="x">Tom & Jerry's</a>"#), "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;");
+    }
+
+
+    #[test]
+    fn descriptor_uses_the_opensearch_placeholder() {
+        let engine = Engine::new("a very long engine name", "https://example.test/?q={{term}}&l=en", "{{term}}", r"\s+", "+");
+        let descriptor = descriptor(&engine);
+        assert!(descriptor.contains("<ShortName>a very long engi</ShortName>"));
+        assert!(descriptor.contains(r#"template="https://example.test/?q={searchTerms}&amp;l=en""#));
+    }
+}
@@ -0,0 +1,123 @@
+use deunicode::deunicode;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{Engine, SearchOptions};
+
+
+/// Replaces the typographic quotes and dashes that word processors substitute for the plain ones, which NFKC
+/// leaves untouched
+fn fold_typography(character: char) -> char {
+    match character {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' => '\'',
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' | '\u{00AB}' | '\u{00BB}' => '"',
+        '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2015}' | '\u{2212}' => '-',
+        _ => character,
+    }
+}
+
+
+/// Whether the character is part of an emoji: pictographs, symbols, regional indicators and the joiners,
+/// variation selectors and tags that combine them
+fn is_emoji(character: char) -> bool {
+    matches!(character,
+        '\u{1F000}'..='\u{1FAFF}' | '\u{2600}'..='\u{27BF}' | '\u{2B00}'..='\u{2BFF}' | '\u{2300}'..='\u{23FF}'
+        | '\u{FE00}'..='\u{FE0F}' | '\u{200D}' | '\u{20E3}' | '\u{E0020}'..='\u{E007F}')
+}
+
+
+/// Whether the character is punctuation, either ASCII or from the general and CJK punctuation blocks
+fn is_punctuation(character: char) -> bool {
+    character.is_ascii_punctuation()
+        || matches!(character, '\u{00A1}' | '\u{00A7}' | '\u{00AB}' | '\u{00B6}' | '\u{00B7}' | '\u{00BB}' | '\u{00BF}'
+            | '\u{2010}'..='\u{2027}' | '\u{2030}'..='\u{205E}' | '\u{3001}'..='\u{3003}' | '\u{3008}'..='\u{3011}')
+}
+
+
+/// Prepares the term before the regex of the engine is applied. The term is always normalized to NFC, so that
+/// text copied from PDFs with decomposed accents searches the same as typed text. With `fold_typography` it's
+/// normalized to NFKC instead and typographic quotes and dashes are replaced by the plain ones. Engines with
+/// `transliterate` receive the term transliterated to ASCII, "São Paulo" becoming "Sao Paulo". Emoji and
+/// punctuation are stripped when asked to by the engine or, if it doesn't say, by the settings. With `--exact` the
+/// term is then wrapped in the exact-match syntax of web engines. The query prefix and suffix of the engine are
+/// finally added around it.
+pub fn apply(term: &str, engine: &Engine, options: &SearchOptions) -> String {
+    let mut term: String = if options.settings.preprocessing.fold_typography {
+        term.nfkc().map(fold_typography).collect()
+    } else {
+        term.nfc().collect()
+    };
+
+    let strip_emoji = engine.strip_emoji.unwrap_or(options.settings.preprocessing.strip_emoji);
+    let strip_punctuation = engine.strip_punctuation.unwrap_or(options.settings.preprocessing.strip_punctuation);
+    if strip_emoji || strip_punctuation {
+        let stripped: String = term.chars()
+            .filter(|character| !(strip_emoji && is_emoji(*character)))
+            .map(|character| if strip_punctuation && is_punctuation(character) { ' ' } else { character })
+            .collect();
+        term = stripped.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+
+    if engine.transliterate {
+        term = deunicode(&term);
+    }
+
+    if options.exact && engine.kind.is_web() {
+        term = engine.exact_term(&term);
+    }
+
+    engine.narrow(&term)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    fn engine() -> Engine {
+        Engine::new("test", "https://test.test/?q={{term}}", "{{term}}", r"\s+", "+")
+    }
+
+
+    #[test]
+    fn macros_are_left_to_the_command_line() {
+        assert_eq!(apply("%clip% and %sel%", &engine(), &SearchOptions::default()), "%clip% and %sel%");
+    }
+
+
+    #[test]
+    fn terms_are_normalized_to_nfc() {
+        assert_eq!(apply("Sa\u{0303}o", &engine(), &SearchOptions::default()), "S\u{00E3}o");
+    }
+
+
+    #[test]
+    fn typography_is_folded_when_asked() {
+        let mut options = SearchOptions::default();
+        options.settings.preprocessing.fold_typography = true;
+        assert_eq!(apply("\u{201C}it\u{2019}s\u{201D} \u{2014} ok", &engine(), &options), "\"it's\" - ok");
+    }
+
+
+    #[test]
+    fn engines_override_the_stripping_of_the_settings() {
+        let mut options = SearchOptions::default();
+        options.settings.preprocessing.strip_punctuation = true;
+        assert_eq!(apply("what's  this?! \u{1F600}", &engine(), &options), "what s this \u{1F600}");
+
+        let mut engine = engine();
+        engine.strip_punctuation = Some(false);
+        engine.strip_emoji = Some(true);
+        assert_eq!(apply("what's this? \u{1F600}", &engine, &options), "what's this?");
+    }
+
+
+    #[test]
+    fn exact_term_is_narrowed_by_the_prefix_and_suffix() {
+        let mut engine = engine();
+        engine.transliterate = true;
+        engine.query_prefix = Some("site:wiki".to_string());
+        let options = SearchOptions { exact: true, ..SearchOptions::default() };
+        assert_eq!(apply("São Paulo", &engine, &options), "site:wiki \"Sao Paulo\"");
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+
+use log::{error, info};
+
+use crate::Engine;
+
+/// Name of the engine set as default by `init` and `reset`
+pub const DEFAULT_ENGINE: &str = "duckduckgo";
+
+/// Built-in engine set, embedded so that it can be installed without network access
+const DEFAULTS: &str = include_str!("presets/defaults.yaml");
+
+/// Domain of Google for the countries that have one of their own, by ISO 3166 country code
+const GOOGLE_DOMAINS: [(&str, &str); 25] = [
+    ("AR", "com.ar"), ("AT", "at"), ("AU", "com.au"), ("BE", "be"), ("BR", "com.br"), ("CA", "ca"), ("CH", "ch"),
+    ("DE", "de"), ("DK", "dk"), ("ES", "es"), ("FI", "fi"), ("FR", "fr"), ("GB", "co.uk"), ("IN", "co.in"),
+    ("IT", "it"), ("JP", "co.jp"), ("KR", "co.kr"), ("MX", "com.mx"), ("NL", "nl"), ("NO", "no"), ("PL", "pl"),
+    ("PT", "pt"), ("RU", "ru"), ("SE", "se"), ("TR", "com.tr"),
+];
+
+/// Named engine sets installable with `add --preset`
+pub const PRESETS: [(&str, &str); 1] = [
+    ("developer", include_str!("presets/developer.yaml")),
+];
+
+
+/// Reads an embedded engine set, each engine with a freshly generated uuid
+fn read(content: &str) -> Vec<Engine> {
+    serde_yaml::from_str(content).unwrap_or_else(|e| {
+        error!("Unable to read the built-in engines. Error: {}", e);
+        vec![]
+    })
+}
+
+
+/// Returns the built-in engine set
+pub fn defaults() -> Vec<Engine> {
+    read(DEFAULTS)
+}
+
+
+/// Returns the engine set of the preset whose name is passed as an argument
+pub fn preset(name: &str) -> Option<Vec<Engine>> {
+    PRESETS.iter().find(|(preset, _)| *preset == name).map(|(_, content)| read(content))
+}
+
+
+/// Language and country of the user, as in the `pt_BR.UTF-8` value of `LANG`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Locale {
+    /// ISO 639 language code, in lowercase
+    pub language: String,
+
+    /// ISO 3166 country code, in uppercase
+    pub country: Option<String>,
+}
+
+
+impl Locale {
+    /// Reads a locale such as `pt_BR.UTF-8`, `pt-BR` or `de`, ignoring the codeset and modifier. The `C` and `POSIX`
+    /// locales, which name no language, are `None`.
+    pub fn parse(value: &str) -> Option<Locale> {
+        let name = value.split(['.', '@']).next().unwrap_or_default().trim();
+        if name.is_empty() || name == "C" || name == "POSIX" {
+            return None;
+        }
+
+        let (language, country) = match name.split_once(['_', '-']) {
+            Some((language, country)) => (language, Some(country.to_uppercase())),
+            None => (name, None),
+        };
+        let valid = |code: &str| (2..=3).contains(&code.len()) && code.chars().all(|c| c.is_ascii_alphabetic());
+        (valid(language) && country.as_deref().is_none_or(valid))
+            .then(|| Locale { language: language.to_lowercase(), country })
+    }
+
+
+    /// Locale of the messages of the system, from the first of `LC_ALL`, `LC_MESSAGES` and `LANG` that is set
+    pub fn detect() -> Option<Locale> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Locale::parse(&value))
+    }
+
+
+    /// Whether the built-in engines already suit the locale, being written for English in the United States
+    pub fn is_default(&self) -> bool {
+        self.language == "en" && self.country.as_deref().is_none_or(|country| country == "US")
+    }
+}
+
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.country {
+            Some(country) => write!(f, "{}_{}", self.language, country),
+            None => write!(f, "{}", self.language),
+        }
+    }
+}
+
+
+/// Adapts the built-in engines to the locale: Wikipedia in its language, Google and Bing with its language and
+/// country set (`hl`/`gl` and `setlang`/`cc`), Google on the domain of the country, and a `news` engine searching
+/// Google News of the country
+pub fn localize(engines: Vec<Engine>, locale: &Locale) -> Vec<Engine> {
+    let language = &locale.language;
+    let mut engines: Vec<Engine> = engines.into_iter()
+        .map(|mut engine| {
+            match engine.name.as_str() {
+                "wikipedia" => {
+                    let host = format!("{}.wikipedia.org", language);
+                    engine.url_pattern = engine.url_pattern.replace("en.wikipedia.org", &host);
+                    engine.suggest_url = engine.suggest_url.map(|url| url.replace("en.wikipedia.org", &host));
+                }
+                "google" => {
+                    let domain = locale.country.as_deref()
+                        .and_then(|country| GOOGLE_DOMAINS.iter().find(|(code, _)| *code == country))
+                        .map_or("com", |(_, domain)| domain);
+                    engine.url_pattern = engine.url_pattern.replace("www.google.com", &format!("www.google.{}", domain));
+                    engine.url_pattern.push_str(&format!("&hl={}", language));
+                    if let Some(country) = &locale.country {
+                        engine.url_pattern.push_str(&format!("&gl={}", country));
+                    }
+                }
+                "bing" => {
+                    engine.url_pattern.push_str(&format!("&setlang={}", language));
+                    if let Some(country) = &locale.country {
+                        engine.url_pattern.push_str(&format!("&cc={}", country));
+                    }
+                }
+                _ => {}
+            }
+            engine
+        })
+        .collect();
+
+    if let Some(country) = &locale.country {
+        let url_pattern = format!(
+            "https://news.google.com/search?q={{{{term}}}}&hl={}-{}&gl={}&ceid={}:{}",
+            language, country, country, country, language,
+        );
+        engines.push(Engine::new("news", &url_pattern, "{{term}}", r"\s+", "+"));
+    }
+
+    info!("Built-in engines localized for {}", locale);
+    engines
+}
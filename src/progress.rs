@@ -0,0 +1,44 @@
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+
+/// Whether progress may be shown at all, cleared by `--plain` and `--json`
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+
+/// Hides every progress bar and spinner from then on
+pub fn disable() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+
+/// Whether progress is shown: unless disabled, when the standard error, on which it's drawn, is a terminal
+fn visible() -> bool {
+    ENABLED.load(Ordering::Relaxed) && io::stderr().is_terminal()
+}
+
+
+/// Progress bar of an operation over `len` items, the message naming the item being processed. Hidden when
+/// progress isn't shown, see [disable].
+pub fn bar(len: u64) -> ProgressBar {
+    if !visible() {
+        return ProgressBar::hidden();
+    }
+    let style = ProgressStyle::with_template("{bar:30} {pos}/{len} {msg}").unwrap_or_else(|_| ProgressStyle::default_bar());
+    ProgressBar::new(len).with_style(style)
+}
+
+
+/// Spinner of an operation whose length is unknown, with the message describing it. Hidden when progress isn't
+/// shown, see [disable].
+pub fn spinner(message: &str) -> ProgressBar {
+    if !visible() {
+        return ProgressBar::hidden();
+    }
+    let spinner = ProgressBar::new_spinner().with_message(message.to_string());
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner
+}
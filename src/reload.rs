@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+
+use log::{error, info, warn};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::Configuration;
+
+
+/// Watches the configuration file, so that long-running modes can reload it when it is edited, for instance
+/// through `search open`, without being restarted
+pub struct ConfigWatcher {
+    /// Path of the watched configuration file
+    path: PathBuf,
+
+    /// Receives the events of the directory containing the file
+    events: Receiver<notify::Result<Event>>,
+
+    /// Kept alive for as long as the events are needed
+    _watcher: RecommendedWatcher,
+}
+
+
+impl ConfigWatcher {
+    /// Starts watching the configuration file. The directory containing it is watched instead of the file itself,
+    /// since editors usually save by replacing the file.
+    pub fn new(path: &Path) -> Result<ConfigWatcher, notify::Error> {
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        let directory = path.parent().unwrap_or(Path::new("."));
+        watcher.watch(directory, RecursiveMode::NonRecursive)?;
+        info!("Watching {:?} for changes", path);
+
+        Ok(ConfigWatcher { path: path.to_path_buf(), events, _watcher: watcher })
+    }
+
+
+    /// Consumes the pending events, returning whether any of them changed the configuration file
+    pub fn changed(&self) -> bool {
+        let mut changed = false;
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event) => {
+                    changed |= (event.kind.is_modify() || event.kind.is_create())
+                        && event.paths.iter().any(|path| path.file_name() == self.path.file_name());
+                }
+                Err(e) => warn!("Error watching the configuration file. Error: {}", e),
+            }
+        }
+        changed
+    }
+
+
+    /// Reloads the configuration if the file changed. The configuration is only replaced once the new one is
+    /// successfully loaded, so a broken edit leaves the previous configuration in place.
+    pub fn reload_if_changed(&self, config: &mut Configuration) {
+        if !self.changed() {
+            return;
+        }
+
+        match Configuration::from(self.path.clone()) {
+            Ok(reloaded) => {
+                info!("Configuration reloaded");
+                *config = reloaded;
+            }
+            Err(e) => {
+                error!("Unable to reload the configuration, keeping the previous one. Error: {}", e);
+                eprintln!("Unable to reload the configuration, keeping the previous one: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::Local;
+use log::{error, info};
+
+use crate::{history, search, session, Configuration, Engine, SearchOptions};
+use crate::state::State;
+
+
+/// Converts the topic into a name suitable for a file, keeping only alphanumeric characters and dashes
+fn slug(topic: &str) -> String {
+    let slug: String = topic.to_lowercase().chars()
+        .map(|character| if character.is_alphanumeric() { character } else { '-' })
+        .collect();
+    slug.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-")
+}
+
+
+/// Entry point of the `research` subcommand. Starts a session named after the topic and searches the topic with
+/// every engine of the group. The session stays active, so the following searches are part of the research, until
+/// it is stopped and the summary is written.
+pub fn start(config: &Configuration, options: &SearchOptions, state: &mut State, topic: &str, group: &str) {
+    let engines: Vec<Engine> = match config.group(group) {
+        Ok(engines) => engines,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    if let Some(previous) = session::start(state, topic) {
+        conclude(config, &options.history_path, state, &previous);
+    }
+    state.research = Some(group.to_string());
+
+    for engine in &engines {
+        search(engine, topic, options, state);
+        state.opened(engine);
+    }
+}
+
+
+/// Writes the summary of the research whose session was just stopped into the notes directory: the topic, the
+/// engines used and every URL opened during the session, with its timestamp. Sessions not started by `research`
+/// are ignored.
+pub fn conclude(config: &Configuration, history_path: &Path, state: &mut State, topic: &str) {
+    let group = match state.research.take() {
+        Some(group) => group,
+        None => return,
+    };
+
+    let entries: Vec<history::Entry> = history::entries(history_path).into_iter()
+        .filter(|entry| entry.session.as_deref() == Some(topic))
+        .collect();
+
+    let mut engines: Vec<&str> = entries.iter().map(|entry| entry.engine.as_str()).collect();
+    engines.sort();
+    engines.dedup();
+
+    let mut summary = format!("# {}\n\n", topic);
+    summary.push_str(&format!("- Group: {}\n", group));
+    summary.push_str(&format!("- Engines: {}\n", engines.join(", ")));
+    if let (Some(first), Some(last)) = (entries.first(), entries.last()) {
+        summary.push_str(&format!("- Started: {}\n", first.timestamp.format("%Y-%m-%d %H:%M:%S")));
+        summary.push_str(&format!("- Finished: {}\n", last.timestamp.format("%Y-%m-%d %H:%M:%S")));
+    }
+    summary.push_str("\n## Searches\n\n");
+    for entry in &entries {
+        summary.push_str(&format!(
+            "- {} [{}: {}]({})\n",
+            entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            entry.engine,
+            entry.term,
+            entry.url,
+        ));
+    }
+
+    let notes_dir = config.notes_dir();
+    let path = notes_dir.join(format!("{}-{}.md", Local::now().format("%Y-%m-%d"), slug(topic)));
+    match fs::create_dir_all(&notes_dir).and_then(|_| fs::write(&path, summary)) {
+        Ok(_) => {
+            info!("Research summary written to {:?}", path);
+            println!("Summary of the research written to {}", path.display());
+        }
+        Err(e) => {
+            error!("Unable to write the research summary to {:?}. Error: {}", path, e);
+            eprintln!("Unable to write the summary of the research");
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::{Duration, Local};
+use log::{error, info};
+use serde_json::json;
+
+use crate::{history, open_url, opensearch, print_json, Configuration, Engine, SearchOptions, SessionFormat};
+use crate::state::State;
+
+
+/// Starts a session, tagging the following searches with its name until it is stopped. Returns the name of the
+/// session that was active before, if any.
+pub fn start(state: &mut State, name: &str) -> Option<String> {
+    let previous = state.session.replace(name.to_string());
+    if let Some(previous) = &previous {
+        println!("Session {} stopped", previous);
+    }
+    info!("Session {} started", name);
+    println!("Session {} started", name);
+    previous
+}
+
+
+/// Stops the active session, if any, returning its name
+pub fn stop(state: &mut State) -> Option<String> {
+    let stopped = state.session.take();
+    match &stopped {
+        Some(name) => {
+            info!("Session {} stopped", name);
+            println!("Session {} stopped", name);
+        }
+        None => eprintln!("There is no active session"),
+    }
+    stopped
+}
+
+
+/// Opens again every URL searched during the session, in the order they were searched, through the openers of the
+/// settings as the searches were. URLs of engines removed since are opened as the ones of plain web engines.
+pub fn open(config: &Configuration, history_path: &Path, name: &str, options: &SearchOptions, state: &mut State) {
+    let entries: Vec<history::Entry> = history::entries(history_path).into_iter()
+        .filter(|entry| entry.session.as_deref() == Some(name))
+        .collect();
+
+    if entries.is_empty() {
+        eprintln!("No searches recorded in the session {}", name);
+        return;
+    }
+
+    for entry in entries {
+        let engine = config.engines.named(&entry.engine).cloned()
+            .unwrap_or_else(|| Engine::new(&entry.engine, &entry.url, "{{term}}", r"\s+", "+"));
+        if open_url(&engine, &entry.url, options, state) {
+            info!("Reopened {}", entry.url);
+        }
+    }
+}
+
+
+/// Searches recorded in the history for each session, by session name
+fn sessions(history_path: &Path) -> BTreeMap<String, Vec<history::Entry>> {
+    let mut sessions: BTreeMap<String, Vec<history::Entry>> = BTreeMap::new();
+    for entry in history::entries(history_path) {
+        if let Some(name) = entry.session.clone() {
+            sessions.entry(name).or_default().push(entry);
+        }
+    }
+    sessions
+}
+
+
+/// Prints every session recorded in the history, with its number of searches and the moments of the first and
+/// the last ones. The active session is marked with `*`, or flagged as `active` in the JSON list printed with
+/// `json`.
+pub fn list(history_path: &Path, state: &State, json: bool) {
+    let sessions = sessions(history_path);
+
+    if json {
+        let sessions: Vec<_> = sessions.iter()
+            .map(|(name, entries)| json!({
+                "name": name,
+                "active": state.session.as_ref() == Some(name),
+                "searches": entries.len(),
+                "first": entries[0].timestamp,
+                "last": entries[entries.len() - 1].timestamp,
+            }))
+            .collect();
+        print_json(&sessions);
+        return;
+    }
+
+    if sessions.is_empty() {
+        println!("No sessions recorded");
+    }
+
+    for (name, entries) in &sessions {
+        let marker = if state.session.as_ref() == Some(name) { "*" } else { " " };
+        let (first, last) = (&entries[0], &entries[entries.len() - 1]);
+        println!(
+            "{} {:<20} {:>4} searches  {} → {}",
+            marker,
+            name,
+            entries.len(),
+            first.timestamp.format("%Y-%m-%d %H:%M"),
+            last.timestamp.format("%Y-%m-%d %H:%M"),
+        );
+    }
+}
+
+
+/// Removes from the history the searches of the session passed as an argument or, with `older_than`, of every
+/// session whose latest search is older than that many days. Searches made outside of sessions are kept.
+pub fn prune(history_path: &Path, name: Option<&str>, older_than: Option<u64>) {
+    let limit = older_than.map(|days| Local::now() - Duration::days(days as i64));
+    let pruned: Vec<String> = sessions(history_path).into_iter()
+        .filter(|(session, entries)| {
+            name == Some(session.as_str()) || limit.is_some_and(|limit| entries.iter().all(|entry| entry.timestamp < limit))
+        })
+        .map(|(session, _)| session)
+        .collect();
+
+    if pruned.is_empty() {
+        println!("No sessions to prune");
+        return;
+    }
+
+    let kept: Vec<history::Entry> = history::entries(history_path).into_iter()
+        .filter(|entry| !entry.session.as_ref().is_some_and(|session| pruned.contains(session)))
+        .collect();
+
+    match history::rewrite(history_path, &kept) {
+        Ok(_) => {
+            info!("Sessions pruned: {:?}", pruned);
+            for session in pruned {
+                println!("Session {} pruned", session);
+            }
+        }
+        Err(e) => {
+            error!("Unable to rewrite the history file. Error: {}", e);
+            eprintln!("Unable to prune the sessions");
+        }
+    }
+}
+
+
+/// Generates a list of links to the searches of the session, each labeled with the engine and the term, to be
+/// pasted into notes or shared. Returns `None` if the session has no searches.
+pub fn export(history_path: &Path, name: &str, format: SessionFormat) -> Option<String> {
+    let sessions = sessions(history_path);
+    let entries = sessions.get(name)?;
+
+    let content = match format {
+        SessionFormat::Markdown => {
+            let mut content = format!("# {}\n\n", name);
+            for entry in entries {
+                content.push_str(&format!(
+                    "- [{}: {}]({}) — {}\n",
+                    entry.engine,
+                    entry.term.replace('[', "\\[").replace(']', "\\]"),
+                    entry.url,
+                    entry.timestamp.format("%Y-%m-%d %H:%M"),
+                ));
+            }
+            content
+        }
+        SessionFormat::Html => {
+            let mut content = format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n  <meta charset=\"UTF-8\">\n  <title>{0}</title>\n</head>\n<body>\n  <h1>{0}</h1>\n  <ul>\n",
+                opensearch::escape(name),
+            );
+            for entry in entries {
+                content.push_str(&format!(
+                    "    <li><a href=\"{}\">{}: {}</a> — {}</li>\n",
+                    opensearch::escape(&entry.url),
+                    opensearch::escape(&entry.engine),
+                    opensearch::escape(&entry.term),
+                    entry.timestamp.format("%Y-%m-%d %H:%M"),
+                ));
+            }
+            content.push_str("  </ul>\n</body>\n</html>\n");
+            content
+        }
+    };
+
+    Some(content)
+}
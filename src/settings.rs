@@ -0,0 +1,225 @@
+use std::io::{self, IsTerminal};
+use std::process::Command;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::devtools;
+use crate::http::HttpSettings;
+
+
+/// Ways a URL can be opened, tried in the order of [Settings::openers] until one applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Opener {
+    /// The browser tab reused by the engines with `reuse_tab`
+    ReuseTab,
+
+    /// A terminal browser, for the engines with `terminal` or when `--terminal-browser` is given
+    Terminal,
+
+    /// The browser command of the settings, if there's one
+    Command,
+
+    /// Printing the URL, when there's no graphical session
+    Print,
+
+    /// The terminal emulator, when running inside kitty: the URL is opened by the terminal emulator itself or, when
+    /// a terminal browser is asked for, in a new pane running it. It comes after the browser command, so that a
+    /// configured browser, and the window placement of `compare`, are kept inside kitty.
+    Emulator,
+
+    /// The default browser of the system
+    System,
+}
+
+
+/// Whether the output is colored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Color {
+    /// Only when writing to a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+
+    /// Always
+    Always,
+
+    /// Never
+    Never,
+}
+
+
+impl Color {
+    /// Whether the standard output should be colored
+    pub fn enabled(self) -> bool {
+        match self {
+            Color::Auto => io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            Color::Always => true,
+            Color::Never => false,
+        }
+    }
+}
+
+
+/// Whether the standard output is a terminal known to support OSC 8 hyperlinks
+fn supports_hyperlinks() -> bool {
+    let variable = |name: &str| std::env::var(name).unwrap_or_default();
+
+    io::stdout().is_terminal()
+        && (["KITTY_WINDOW_ID", "WEZTERM_EXECUTABLE", "WT_SESSION", "KONSOLE_VERSION", "GHOSTTY_RESOURCES_DIR"].iter()
+                .any(|name| std::env::var_os(name).is_some())
+            || matches!(variable("TERM_PROGRAM").as_str(), "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper" | "Tabby")
+            || matches!(variable("TERM").as_str(), "xterm-kitty" | "xterm-ghostty" | "foot" | "foot-extra" | "alacritty")
+            || variable("VTE_VERSION").parse::<u32>().is_ok_and(|version| version >= 5000))
+}
+
+
+/// How several terms given on the command line are searched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MultiTerm {
+    /// Every term is searched on its own, `search rust borrow checker` opening three searches
+    #[default]
+    Separate,
+
+    /// The terms are joined into a single query
+    Join,
+}
+
+
+/// What is done with the URLs longer than [Settings::max_url_length]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LongUrls {
+    /// The term is truncated at a word boundary until the URL fits, with a warning
+    #[default]
+    Truncate,
+
+    /// The search is aborted with an error
+    Abort,
+}
+
+
+/// How the terms are prepared before the regex of the engine is applied, see [crate::preprocess::apply]. The
+/// engines can override the stripping of emoji and punctuation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preprocessing {
+    /// Whether terms are normalized to NFKC and their typographic quotes and dashes folded into the plain ones
+    pub fold_typography: bool,
+
+    /// Whether emoji are stripped from the terms
+    pub strip_emoji: bool,
+
+    /// Whether punctuation is stripped from the terms
+    pub strip_punctuation: bool,
+}
+
+
+/// Settings affecting how the searches behave, kept in the `settings` section of the configuration file. Every
+/// setting has a default, so the section, or any of its keys, can be left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Command the URLs are opened with instead of the default browser, with `{url}` and `{side}` placeholders
+    pub browser_command: Option<String>,
+
+    /// Remote debugging endpoint of the browser used by the engines with `reuse_tab`, [devtools::DEFAULT_ENDPOINT]
+    /// if null
+    pub devtools_endpoint: Option<String>,
+
+    /// Ways the URLs are opened, in the order they are tried
+    pub openers: Vec<Opener>,
+
+    /// Whether the printed URLs are written as OSC 8 hyperlinks, detected from the terminal if null
+    pub hyperlink: Option<bool>,
+
+    /// Whether `--copy` copies to the clipboard of the local machine through the terminal emulator, with an OSC 52
+    /// escape sequence, inside SSH sessions without a clipboard
+    pub osc52: bool,
+
+    /// Maximum size, once encoded, of the text copied through the terminal emulator
+    pub osc52_max_bytes: usize,
+
+    /// Whether a desktop notification is shown when a URL can't be opened, for searches started from a hotkey,
+    /// whose errors would otherwise go unnoticed
+    pub notifications: bool,
+
+    /// Whether the searches are recorded in the history
+    pub history: bool,
+
+    /// Milliseconds `watch` waits before searching a copied text, during which the search can be cancelled
+    pub watch_grace_ms: u64,
+
+    /// How several terms given on the command line are searched
+    pub multi_term: MultiTerm,
+
+    /// Maximum length of the generated URLs, past which browsers and servers silently refuse them, 0 for no limit
+    pub max_url_length: usize,
+
+    /// What is done with the URLs longer than the maximum
+    pub long_urls: LongUrls,
+
+    /// How the terms are prepared
+    pub preprocessing: Preprocessing,
+
+    /// Settings of the requests made over the network
+    pub http: HttpSettings,
+
+    /// Whether the output is colored
+    pub color: Color,
+
+    /// Whether `list` shows the favicons of the engines, downloaded in the background the first time
+    pub favicons: bool,
+}
+
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            browser_command: None,
+            devtools_endpoint: None,
+            openers: vec![Opener::ReuseTab, Opener::Terminal, Opener::Command, Opener::Print, Opener::Emulator, Opener::System],
+            hyperlink: None,
+            osc52: true,
+            osc52_max_bytes: 100_000,
+            notifications: false,
+            history: true,
+            watch_grace_ms: 3000,
+            multi_term: MultiTerm::Separate,
+            max_url_length: 2000,
+            long_urls: LongUrls::Truncate,
+            preprocessing: Preprocessing::default(),
+            http: HttpSettings::default(),
+            color: Color::Auto,
+            favicons: false,
+        }
+    }
+}
+
+
+impl Settings {
+    /// Remote debugging endpoint of the browser used by the engines with `reuse_tab`
+    pub fn devtools_endpoint(&self) -> String {
+        self.devtools_endpoint.clone().unwrap_or_else(|| devtools::DEFAULT_ENDPOINT.to_string())
+    }
+
+
+    /// Whether the printed URLs are written as OSC 8 hyperlinks: always when `forced`, as given by `--hyperlink`,
+    /// otherwise as the settings say or, if they don't, when the terminal is known to support them
+    pub fn hyperlinks(&self, forced: bool) -> bool {
+        forced || self.hyperlink.unwrap_or_else(supports_hyperlinks)
+    }
+
+
+    /// Shows a desktop notification with notify-send, if notifications are enabled
+    pub fn notify(&self, summary: &str, body: &str) {
+        if !self.notifications {
+            return;
+        }
+        if let Err(e) = Command::new("notify-send").args(["--app-name", "search", summary, body]).spawn() {
+            warn!("Unable to show a notification. Error: {}", e);
+        }
+    }
+}
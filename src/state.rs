@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::Engine;
+
+
+/// State kept between invocations, stored apart from the configuration since it changes on every search and is
+/// not meant to be edited by hand
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct State {
+    /// Path of the state file
+    #[serde(skip)]
+    path: PathBuf,
+
+    /// Moment in which each engine was last used, by uuid
+    #[serde(default)]
+    last_opened: BTreeMap<Uuid, DateTime<Local>>,
+
+    /// Identifier of the browser tab reused by the engines with `reuse_tab`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_tab: Option<String>,
+
+    /// Name of the active session, whose name tags the searches recorded in the history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+
+    /// Group searched when the active session was started by `research`, in which case a summary is written when
+    /// the session is stopped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub research: Option<String>,
+
+    /// Number of searches made with `@rotate`, used to pick the next engine of the rotation
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub rotation: usize,
+}
+
+
+/// Whether the counter is zero, so that it's omitted from the state file
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+
+impl State {
+    /// Loads the state from the file passed as an argument, starting from an empty state if the file does not
+    /// exist or can't be read
+    pub fn load(path: PathBuf) -> State {
+        let mut state: State = match File::open(&path) {
+            Ok(file) => serde_yaml::from_reader(file).unwrap_or_else(|e| {
+                error!("Unable to read the state file, starting from an empty state. Error: {}", e);
+                State::default()
+            }),
+            Err(_) => State::default(),
+        };
+        state.path = path;
+        state
+    }
+
+
+    /// Saves the state to its file
+    pub fn save(&self) -> Result<(), io::Error> {
+        let file = File::create(&self.path)?;
+        serde_yaml::to_writer(file, self).map_err(io::Error::other)
+    }
+
+
+    /// Whether the engine was used less than its cooldown ago, in which case the search should be ignored
+    pub fn cooling_down(&self, engine: &Engine) -> bool {
+        match (engine.cooldown_secs, self.last_opened.get(&engine.uuid)) {
+            (Some(cooldown), Some(last)) => {
+                let elapsed = Local::now().signed_duration_since(*last).num_seconds();
+                elapsed >= 0 && (elapsed as u64) < cooldown
+            }
+            _ => false,
+        }
+    }
+
+
+    /// Records that the engine was just used
+    pub fn opened(&mut self, engine: &Engine) {
+        info!("Recording the use of {}", engine.name);
+        self.last_opened.insert(engine.uuid, Local::now());
+    }
+}
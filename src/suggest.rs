@@ -0,0 +1,58 @@
+use std::io;
+
+use log::{info, warn};
+use serde_json::Value;
+
+use crate::cache::Cache;
+use crate::{http, Engine};
+
+
+/// Kind of the responses cached by the suggestions, see [Cache]
+const KIND: &str = "suggest";
+
+
+/// Reads the suggestions out of a response in the OpenSearch suggestions format, `["term", ["first", "second"]]`,
+/// or of a plain list of suggestions
+fn parse(body: &str) -> Result<Vec<String>, io::Error> {
+    let value: Value = serde_json::from_str(body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let suggestions = match &value {
+        Value::Array(items) if items.len() >= 2 && items[0].is_string() && items[1].is_array() => &items[1],
+        _ => &value,
+    };
+
+    suggestions.as_array()
+        .map(|items| items.iter().filter_map(|item| item.as_str().map(String::from)).collect())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Not a list of suggestions"))
+}
+
+
+/// Returns the suggestions of the engine for the term, from its `suggest_url`. Responses are cached for a while,
+/// see [Cache], and when the request fails or times out, as when offline, the expired ones are used instead.
+pub fn fetch(engine: &Engine, term: &str, settings: &http::HttpSettings, cache: &Cache) -> Result<Vec<String>, io::Error> {
+    let url = engine.suggest_url(term)?;
+
+    if let Some(body) = cache.get(KIND, &engine.name, term) {
+        return parse(&body);
+    }
+
+    info!("Requesting the suggestions of {} for {}", engine.name, term);
+    let agent = http::agent(settings)?.build();
+    let response = http::get(&agent, engine, &url).call()
+        .map_err(io::Error::other)
+        .and_then(|response| response.into_string());
+
+    match response {
+        Ok(body) => {
+            let suggestions = parse(&body)?;
+            cache.put(KIND, &engine.name, term, &body);
+            Ok(suggestions)
+        }
+        Err(e) => match cache.get_stale(KIND, &engine.name, term) {
+            Some(body) => {
+                warn!("Unable to request the suggestions, using the expired cached ones. Error: {}", e);
+                parse(&body)
+            }
+            None => Err(e),
+        },
+    }
+}
@@ -0,0 +1,147 @@
+use std::fs;
+use std::path::PathBuf;
+
+use home::home_dir;
+use log::{info, warn};
+use regex::Regex;
+
+use crate::Engine;
+
+/// Pattern written in the url pattern of the imported engines
+const PATTERN: &str = "{{term}}";
+
+
+/// Directories in which surfraw installs its elvi, followed by the user elvi directory
+pub fn default_directories() -> Vec<PathBuf> {
+    let mut directories = vec![PathBuf::from("/usr/lib/surfraw"), PathBuf::from("/usr/local/lib/surfraw")];
+    if let Some(home) = home_dir() {
+        directories.push(home.join(".config").join("surfraw").join("elvi"));
+    }
+    directories
+}
+
+
+/// Converts the elvi script passed as an argument into an [Engine], if its search URL can be recognized. Only
+/// elvi browsing a single URL built around the escaped arguments are supported.
+fn convert(name: &str, script: &str) -> Option<Engine> {
+    let browse = Regex::new(r#"w3_browse_url\s+"([^"]*)\$\{?escaped_args\}?([^"]*)""#).ok()?;
+    let captures = browse.captures(script)?;
+    let (prefix, suffix) = (&captures[1], &captures[2]);
+
+    if prefix.contains('$') || suffix.contains('$') {
+        info!("Skipping the elvi {}, its URL depends on options", name);
+        return None;
+    }
+
+    Some(Engine::new(name, format!("{}{}{}", prefix, PATTERN, suffix).as_str(), PATTERN, r"\s+", "+"))
+}
+
+
+/// Scans the directories passed as arguments for elvi, converting the ones whose URL can be recognized
+pub fn scan(directories: &[PathBuf]) -> Vec<Engine> {
+    let mut engines = vec![];
+
+    for directory in directories.iter().filter(|directory| directory.is_dir()) {
+        info!("Scanning {:?} for elvi", directory);
+        let entries = match fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("Unable to read {:?}. Error: {}", directory, e);
+                continue;
+            }
+        };
+
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).filter(|path| path.is_file()).collect();
+        paths.sort();
+
+        for path in paths {
+            let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+            match fs::read_to_string(&path) {
+                Ok(script) => engines.extend(convert(&name, &script)),
+                Err(e) => warn!("Unable to read {:?}. Error: {}", path, e),
+            }
+        }
+    }
+
+    engines
+}
+
+
+/// Escapes the characters that are special inside a double-quoted shell string, newlines included
+fn escape(value: &str) -> String {
+    single_line(value).replace('\\', "\\\\").replace('"', "\\\"").replace('$', "\\$").replace('`', "\\`")
+}
+
+
+/// Replaces the control characters, newlines included, by spaces, so that the value can't leave the comment or
+/// heredoc line it is written in
+fn single_line(value: &str) -> String {
+    value.chars().map(|c| if c.is_control() { ' ' } else { c }).collect()
+}
+
+
+/// Generates an elvi wrapper for the engine, to be placed in the surfraw elvi directory. Surfraw escapes the
+/// search words itself, so the regex and replacement of the engine are not part of the elvi. The name and home URL
+/// of the engine only appear in a comment, a quoted heredoc and escaped double-quoted strings, so that nothing in
+/// them is run by the shell.
+pub fn elvi(engine: &Engine) -> String {
+    let (prefix, suffix) = engine.url_pattern.split_once(&engine.pattern).unwrap_or((&engine.url_pattern, ""));
+    let name = single_line(&engine.name);
+    let home = single_line(&engine.home());
+
+    format!(r#"#!/bin/sh
+# elvis: {name}	-- Search {name} ({home})
+. surfraw || exit 1
+
+w3_usage_hook () {{
+    echo "Usage: $w3_argv0 [options] [search words]..."
+    cat <<'EOF'
+Description:
+  Search {name} ({home})
+EOF
+    w3_global_usage
+}}
+
+w3_config
+w3_parse_args "$@"
+if test -z "$w3_args"; then
+    w3_browse_url "{escaped_home}"
+else
+    escaped_args=`w3_url_of_arg $w3_args`
+    w3_browse_url "{prefix}${{escaped_args}}{suffix}"
+fi
+"#, name = name, home = home, escaped_home = escape(&home), prefix = escape(prefix), suffix = escape(suffix))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn escape_neutralizes_double_quoted_strings() {
+        assert_eq!(escape(r#"a"$(id)`id`\"#), r#"a\"\$(id)\`id\`\\"#);
+        assert_eq!(escape("a\nb"), "a b");
+    }
+
+
+    #[test]
+    fn elvi_keeps_name_and_home_out_of_the_shell() {
+        let engine = Engine::new("evil$(touch x)\nrm -rf ~", "https://evil.test/`id`?q={{term}}", "{{term}}", r"\s+", "+");
+        let script = elvi(&engine);
+
+        assert!(script.contains("cat <<'EOF'"));
+        assert!(script.contains("# elvis: evil$(touch x) rm -rf ~"));
+        assert!(!script.lines().any(|line| line.starts_with("rm ")));
+        assert!(script.contains(r#"w3_browse_url "https://evil.test/\`id\`?q=${escaped_args}""#));
+    }
+
+
+    #[test]
+    fn convert_reads_the_url_of_an_elvi() {
+        let script = "w3_browse_url \"https://example.test/search?q=${escaped_args}&lang=en\"";
+        assert_eq!(convert("example", script).unwrap().url_pattern, "https://example.test/search?q={{term}}&lang=en");
+        assert!(convert("example", "w3_browse_url \"https://$host/?q=${escaped_args}\"").is_none());
+    }
+}
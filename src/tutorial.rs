@@ -0,0 +1,143 @@
+use std::io;
+
+use inquire::{Confirm, Text};
+use log::{info, warn};
+use regex::Regex;
+
+use crate::{add_engine, Configuration, Engine};
+
+
+/// Term the URLs previewed during the tutorial are generated for
+const SAMPLE_TERM: &str = "borrow checker";
+
+
+/// Asks the question, with an explanation below it and a default answer. Fails if the user cancels the tutorial.
+fn ask(question: &str, help: &str, default: &str) -> Result<String, io::Error> {
+    Text::new(question)
+        .with_help_message(help)
+        .with_default(default)
+        .prompt()
+        .map_err(io::Error::other)
+}
+
+
+/// Asks a yes or no question. Fails if the user cancels the tutorial.
+fn confirm(question: &str, default: bool) -> Result<bool, io::Error> {
+    Confirm::new(question).with_default(default).prompt().map_err(io::Error::other)
+}
+
+
+/// Asks for the regex until one that compiles is given
+fn ask_regex() -> Result<String, io::Error> {
+    loop {
+        let regex = ask(
+            "Regex applied to the term:",
+            "every match is replaced before the term is placed in the URL, \\s+ matching the runs of spaces",
+            r"\s+",
+        )?;
+        match Regex::new(&regex) {
+            Ok(_) => return Ok(regex),
+            Err(e) => println!("That regex doesn't compile, try again:\n{}\n", e),
+        }
+    }
+}
+
+
+/// Walks the user through the url pattern, pattern, regex and replacement of an engine, showing the URL generated
+/// for [SAMPLE_TERM] until the user is happy with it
+fn build_engine() -> Result<Engine, io::Error> {
+    println!("\nAn engine is a URL with a placeholder, the pattern, replaced by the search term.");
+    let name = ask("Name of the engine:", "used with -e NAME to pick it", "crates")?;
+    let pattern = ask(
+        "Pattern:",
+        "placeholder of the url pattern replaced by the term",
+        "{{term}}",
+    )?;
+    let url_pattern = ask(
+        "Url pattern:",
+        "the URL of a search made in the browser, with the term replaced by the pattern",
+        &format!("https://crates.io/search?q={}", pattern),
+    )?;
+    if !url_pattern.contains(&pattern) {
+        println!("The url pattern doesn't contain the pattern {}, so the term would never reach the URL.", pattern);
+    }
+
+    println!("\nBefore being placed in the URL, the term goes through a regex: every match is replaced by the \
+        replacement, typically turning spaces into + or %20.");
+    loop {
+        let regex = ask_regex()?;
+        let replacement = ask("Replacement of the matches:", "+ and %20 both stand for a space in URLs", "+")?;
+
+        let engine = Engine::new(&name, &url_pattern, &pattern, &regex, &replacement);
+        match engine.url(SAMPLE_TERM) {
+            Ok(url) => println!("\n\"{}\" would be searched at:\n  {}\n", SAMPLE_TERM, url),
+            Err(e) => println!("\nNo URL could be generated: {}\n", e),
+        }
+        if confirm("Does the URL look right?", true)? {
+            return Ok(engine);
+        }
+        println!("Let's try another regex and replacement.");
+    }
+}
+
+
+/// Prints how to bind the search of the selected text to a hotkey on the current desktop
+fn print_hotkey_instructions() {
+    println!("\nSearching the selected text is the point of `search` without a term: bind it to a hotkey.");
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default().to_lowercase();
+
+    if desktop.contains("gnome") {
+        println!("On GNOME, in Settings > Keyboard > Keyboard Shortcuts > Custom Shortcuts, add one running `search`.");
+    } else if desktop.contains("kde") {
+        println!("On KDE Plasma, in System Settings > Shortcuts > Add New > Command or Script, add one running `search`.");
+    } else if std::env::var_os("SWAYSOCK").is_some() || desktop.contains("sway") {
+        println!("On sway, add to ~/.config/sway/config:\n  bindsym $mod+s exec search");
+    } else if desktop.contains("i3") {
+        println!("On i3, add to ~/.config/i3/config:\n  bindsym $mod+s exec search");
+    } else {
+        println!("Add a keyboard shortcut running `search` in the settings of your desktop, or to the configuration \
+            of your window manager, as `bindsym $mod+s exec search` on i3 and sway.");
+    }
+    println!("Then select some text anywhere and press the hotkey.");
+}
+
+
+/// Entry point of the `tutorial` subcommand. Guides the user through adding an engine, with the URL it generates
+/// previewed as the regex and replacement are chosen, making it the default one and binding a hotkey. Cancelling
+/// with Esc or Ctrl-C before the engine is added leaves the configuration untouched.
+pub fn run(config: &mut Configuration) {
+    println!("Welcome! This tutorial adds a search engine step by step. Press Esc at any time to leave.");
+
+    let result = build_engine().and_then(|engine| {
+        let name = engine.name.clone();
+        let replace = !config.contains(&name)
+            || confirm(&format!("An engine named {} already exists, replace it?", name), false)?;
+        if !replace {
+            return Ok(());
+        }
+
+        if config.contains(&name) {
+            config.replace(engine);
+        } else if !add_engine(config, engine, true)? {
+            return Ok(());
+        }
+        println!("Engine {} added, search with it with `search -e {} TERM`.", name, name);
+
+        if confirm(&format!("Make {} the default engine, used when none is given?", name), config.default().is_none())? {
+            config.set_default(name.clone())?;
+            info!("Default engine set to {} by the tutorial", name);
+        }
+        Ok(())
+    });
+
+    match result {
+        Ok(_) => {
+            print_hotkey_instructions();
+            println!("\nThat's it! `search --help` lists everything else.");
+        }
+        Err(e) => {
+            warn!("Tutorial left. Error: {}", e);
+            println!("Tutorial left, see you next time.");
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use log::{error, info, warn};
+
+use crate::reload::ConfigWatcher;
+use crate::{clipboard, query, Configuration, QueryArgs};
+
+
+/// Interval between two reads of the clipboard
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+
+/// Gives the user the grace period to cancel the search of the term, through a desktop notification with a cancel
+/// action. Returns whether the search should go on. Without notify-send, the search goes on after the grace period.
+fn undo_window(term: &str, grace: Duration) -> bool {
+    let notification = Command::new("notify-send")
+        .args(["--app-name", "search", "--wait", "--action", "cancel=Cancel"])
+        .arg(format!("--expire-time={}", grace.as_millis()))
+        .args(["Searching", term])
+        .stdout(Stdio::piped())
+        .spawn();
+
+    let mut notification = match notification {
+        Ok(notification) => notification,
+        Err(e) => {
+            warn!("Unable to show the notification, searching after the grace period. Error: {}", e);
+            sleep(grace);
+            return true;
+        }
+    };
+
+    let deadline = Instant::now() + grace;
+    while Instant::now() < deadline {
+        match notification.try_wait() {
+            Ok(Some(_)) => {
+                let mut action = String::new();
+                if let Some(mut stdout) = notification.stdout.take() {
+                    let _ = stdout.read_to_string(&mut action);
+                }
+                if action.trim() == "cancel" {
+                    info!("Search of {} cancelled", term);
+                    return false;
+                }
+                sleep(deadline.saturating_duration_since(Instant::now()));
+                return true;
+            }
+            Ok(None) => sleep(Duration::from_millis(50)),
+            Err(e) => {
+                warn!("Unable to wait for the notification. Error: {}", e);
+                break;
+            }
+        }
+    }
+
+    let _ = notification.kill();
+    let _ = notification.wait();
+    true
+}
+
+
+/// Search of the copied term. Its macros are not expanded, a copied `%clip%` being searched as it is.
+fn query_args(engine: Option<String>, term: &str) -> QueryArgs {
+    QueryArgs {
+        engine,
+        term: Some(vec![term.to_string()]),
+        ..QueryArgs::default()
+    }
+}
+
+
+/// Entry point of the `watch` subcommand. Searches every text copied to the clipboard from then on with the engine
+/// passed as an argument, the default one if none is. Before each search a notification gives the grace period of
+/// the settings to cancel it, so accidental copies don't spawn unwanted searches. Edits of the configuration are
+/// picked up without restarting.
+pub fn run(config: &mut Configuration, search_dir: &Path, engine: Option<String>) {
+    let watcher = ConfigWatcher::new(&config.file_path)
+        .inspect_err(|e| warn!("Unable to watch the configuration file. Error: {}", e))
+        .ok();
+
+    let mut previous = clipboard::paste().unwrap_or_default();
+    println!("Watching the clipboard, press Ctrl+C to stop");
+
+    loop {
+        sleep(POLL_INTERVAL);
+        if let Some(watcher) = &watcher {
+            watcher.reload_if_changed(config);
+        }
+
+        let text = match clipboard::paste() {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("Unable to read the clipboard. Error: {}", e);
+                continue;
+            }
+        };
+        if text == previous {
+            continue;
+        }
+        previous = text.clone();
+
+        let term = text.trim();
+        if term.is_empty() || term.contains('\n') {
+            continue;
+        }
+
+        if undo_window(term, Duration::from_millis(config.settings.watch_grace_ms)) {
+            if let Err(e) = query(config, search_dir, query_args(engine.clone(), term)) {
+                error!("Unable to search the copied text. Error: {}", e);
+                eprintln!("Unable to search the copied text: {}", e);
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::search_terms;
+
+
+    #[test]
+    fn copied_macros_are_searched_as_they_are() {
+        let config = Configuration::new(PathBuf::from("/nonexistent/search_config.yaml"), None, vec![]);
+        let args = query_args(None, "%clip% %sel% %date%");
+        let terms = search_terms(&config, &args, args.term.clone().unwrap_or_default());
+        assert_eq!(terms, ["%clip% %sel% %date%"]);
+    }
+}